@@ -0,0 +1,47 @@
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// The algorithm of `HttpResponse::body_hash`. See `HttpRequestOptions::body_hash_algorithm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyHashAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// Hashes the body while it is being read.
+pub(crate) enum BodyHasher {
+    Sha256(Sha256),
+    Sha384(Sha384),
+    Sha512(Sha512),
+}
+
+impl BodyHasher {
+    #[inline]
+    pub(crate) fn new(algorithm: BodyHashAlgorithm) -> BodyHasher {
+        match algorithm {
+            BodyHashAlgorithm::Sha256 => BodyHasher::Sha256(Sha256::new()),
+            BodyHashAlgorithm::Sha384 => BodyHasher::Sha384(Sha384::new()),
+            BodyHashAlgorithm::Sha512 => BodyHasher::Sha512(Sha512::new()),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            BodyHasher::Sha256(hasher) => hasher.update(data),
+            BodyHasher::Sha384(hasher) => hasher.update(data),
+            BodyHasher::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    /// The digest in lowercase hexadecimal.
+    pub(crate) fn finish(self) -> String {
+        let digest = match self {
+            BodyHasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            BodyHasher::Sha384(hasher) => hasher.finalize().to_vec(),
+            BodyHasher::Sha512(hasher) => hasher.finalize().to_vec(),
+        };
+
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hyper::net::{NetworkConnector, NetworkStream};
+
+use crate::connector::{ConnectTarget, ConnectionStream, Connector};
+
+const MAX_IDLE_CONNECTIONS_PER_HOST: usize = 8;
+
+type PoolKey = (String, String, u16, ConnectTarget);
+
+/// Idle keep-alive connections which can be reused by later requests to the same scheme, host and port, which connect the same way (the same `resolve` address, `unix_socket` and `local_address` options). Clones share the same connections. See `HttpRequestOptions::connection_pool`.
+#[derive(Clone, Default)]
+pub struct ConnectionPool {
+    idle: Arc<Mutex<HashMap<PoolKey, Vec<ConnectionStream>>>>,
+}
+
+impl ConnectionPool {
+    #[inline]
+    pub fn new() -> ConnectionPool {
+        ConnectionPool::default()
+    }
+
+    /// The count of idle connections in the pool.
+    #[inline]
+    pub fn idle_count(&self) -> usize {
+        self.idle.lock().unwrap().values().map(|streams| streams.len()).sum()
+    }
+
+    /// Close all idle connections.
+    #[inline]
+    pub fn clear(&self) {
+        self.idle.lock().unwrap().clear();
+    }
+
+    fn take(&self, key: &PoolKey) -> Option<ConnectionStream> {
+        let mut idle = self.idle.lock().unwrap();
+
+        let streams = idle.get_mut(key)?;
+
+        while let Some(stream) = streams.pop() {
+            // the server may have closed the connection while it was idle
+            if !stream.is_stale() {
+                return Some(stream);
+            }
+        }
+
+        None
+    }
+
+    fn put(&self, key: PoolKey, stream: ConnectionStream) {
+        let mut idle = self.idle.lock().unwrap();
+
+        let streams = idle.entry(key).or_default();
+
+        if streams.len() < MAX_IDLE_CONNECTIONS_PER_HOST {
+            streams.push(stream);
+        }
+    }
+}
+
+impl Debug for ConnectionPool {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("ConnectionPool").field("idle_count", &self.idle_count()).finish()
+    }
+}
+
+/// Reuses the idle connections of a `ConnectionPool`, and makes new ones with a `Connector`.
+pub(crate) struct PooledConnector {
+    pub(crate) connector: Connector,
+    pub(crate) pool: ConnectionPool,
+}
+
+impl NetworkConnector for PooledConnector {
+    type Stream = PooledStream;
+
+    fn connect(&self, host: &str, port: u16, scheme: &str) -> hyper::Result<PooledStream> {
+        let key = (scheme.to_string(), host.to_string(), port, self.connector.target(host));
+
+        let stream = match self.pool.take(&key) {
+            Some(mut stream) => {
+                self.connector.record_peer_addr(&mut stream);
+
+                stream
+            }
+            None => self.connector.connect(host, port, scheme)?,
+        };
+
+        Ok(PooledStream {
+            stream: Some(stream),
+            key,
+            pool: self.pool.clone(),
+            is_closed: false,
+        })
+    }
+}
+
+/// A connection which goes back to its pool when it is dropped, unless it has been closed (hyper closes a connection which cannot be kept alive).
+pub(crate) struct PooledStream {
+    stream: Option<ConnectionStream>,
+    key: PoolKey,
+    pool: ConnectionPool,
+    is_closed: bool,
+}
+
+impl PooledStream {
+    #[inline]
+    fn stream(&self) -> &ConnectionStream {
+        self.stream.as_ref().unwrap()
+    }
+
+    #[inline]
+    fn stream_mut(&mut self) -> &mut ConnectionStream {
+        self.stream.as_mut().unwrap()
+    }
+}
+
+impl Read for PooledStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let c = self.stream_mut().read(buf)?;
+
+        if c == 0 {
+            // the server has closed the connection
+            self.is_closed = true;
+        }
+
+        Ok(c)
+    }
+}
+
+impl Write for PooledStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream_mut().write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream_mut().flush()
+    }
+}
+
+impl NetworkStream for PooledStream {
+    #[inline]
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        self.stream_mut().peer_addr()
+    }
+
+    #[inline]
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.stream().set_read_timeout(dur)
+    }
+
+    #[inline]
+    fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.stream().set_write_timeout(dur)
+    }
+
+    #[inline]
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        self.is_closed = true;
+
+        self.stream_mut().close(how)
+    }
+}
+
+impl Drop for PooledStream {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.is_closed {
+            if let Some(stream) = self.stream.take() {
+                self.pool.put(self.key.clone(), stream);
+            }
+        }
+    }
+}
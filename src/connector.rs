@@ -0,0 +1,452 @@
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hyper::net::{HttpStream, NetworkConnector, NetworkStream, SslClient};
+use hyper_native_tls::{NativeTlsClient, TlsStream};
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+
+use crate::timings::ConnectionTimings;
+use crate::HttpRequestOptions;
+
+/// The transport of a connection, before TLS.
+#[derive(Debug)]
+pub(crate) enum PlainStream {
+    Tcp(HttpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl PlainStream {
+    /// Whether the other side has closed the connection (or sent something unexpected), checked without blocking.
+    fn is_stale(&self) -> bool {
+        match self {
+            PlainStream::Tcp(stream) => {
+                if stream.0.set_nonblocking(true).is_err() {
+                    return true;
+                }
+
+                let result = stream.0.peek(&mut [0u8; 1]);
+
+                if stream.0.set_nonblocking(false).is_err() {
+                    return true;
+                }
+
+                !matches!(result, Err(ref err) if err.kind() == io::ErrorKind::WouldBlock)
+            }
+            // peeking a Unix socket is not stable yet
+            #[cfg(unix)]
+            PlainStream::Unix(_) => false,
+        }
+    }
+}
+
+impl Clone for PlainStream {
+    #[inline]
+    fn clone(&self) -> PlainStream {
+        match self {
+            PlainStream::Tcp(stream) => PlainStream::Tcp(stream.clone()),
+            #[cfg(unix)]
+            PlainStream::Unix(stream) => PlainStream::Unix(stream.try_clone().unwrap()),
+        }
+    }
+}
+
+impl Read for PlainStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            PlainStream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            PlainStream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for PlainStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            PlainStream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            PlainStream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            PlainStream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            PlainStream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+impl NetworkStream for PlainStream {
+    #[inline]
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        match self {
+            PlainStream::Tcp(stream) => stream.peer_addr(),
+            #[cfg(unix)]
+            PlainStream::Unix(_) => Err(io::Error::other("A Unix socket has no IP address.")),
+        }
+    }
+
+    #[inline]
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        match self {
+            PlainStream::Tcp(stream) => stream.set_read_timeout(dur),
+            #[cfg(unix)]
+            PlainStream::Unix(stream) => stream.set_read_timeout(dur),
+        }
+    }
+
+    #[inline]
+    fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        match self {
+            PlainStream::Tcp(stream) => stream.set_write_timeout(dur),
+            #[cfg(unix)]
+            PlainStream::Unix(stream) => stream.set_write_timeout(dur),
+        }
+    }
+
+    #[inline]
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        match self {
+            PlainStream::Tcp(stream) => stream.close(how),
+            #[cfg(unix)]
+            PlainStream::Unix(stream) => {
+                match stream.shutdown(how) {
+                    Err(ref err) if err.kind() == io::ErrorKind::NotConnected => Ok(()),
+                    result => result,
+                }
+            }
+        }
+    }
+}
+
+/// The stream of a connection made by `Connector`.
+#[derive(Debug, Clone)]
+pub(crate) enum ConnectionStream {
+    Http(PlainStream),
+    Https(TlsStream<PlainStream>),
+}
+
+impl ConnectionStream {
+    /// Whether an idle connection can no longer be used.
+    #[inline]
+    pub(crate) fn is_stale(&self) -> bool {
+        match self {
+            ConnectionStream::Http(stream) => stream.is_stale(),
+            ConnectionStream::Https(stream) => stream.lock().get_ref().is_stale(),
+        }
+    }
+}
+
+impl Read for ConnectionStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            ConnectionStream::Http(stream) => stream.read(buf),
+            ConnectionStream::Https(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for ConnectionStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ConnectionStream::Http(stream) => stream.write(buf),
+            ConnectionStream::Https(stream) => stream.write(buf),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ConnectionStream::Http(stream) => stream.flush(),
+            ConnectionStream::Https(stream) => stream.flush(),
+        }
+    }
+}
+
+impl NetworkStream for ConnectionStream {
+    #[inline]
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        match self {
+            ConnectionStream::Http(stream) => stream.peer_addr(),
+            ConnectionStream::Https(stream) => stream.peer_addr(),
+        }
+    }
+
+    #[inline]
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        match self {
+            ConnectionStream::Http(stream) => stream.set_read_timeout(dur),
+            ConnectionStream::Https(stream) => stream.set_read_timeout(dur),
+        }
+    }
+
+    #[inline]
+    fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        match self {
+            ConnectionStream::Http(stream) => stream.set_write_timeout(dur),
+            ConnectionStream::Https(stream) => stream.set_write_timeout(dur),
+        }
+    }
+
+    #[inline]
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        match self {
+            ConnectionStream::Http(stream) => stream.close(how),
+            ConnectionStream::Https(stream) => stream.lock().get_mut().close(how),
+        }
+    }
+}
+
+pub(crate) type CapturedStream = Arc<Mutex<Option<ConnectionStream>>>;
+
+/// Where a connection to a host actually goes, apart from the scheme, the host and the port, so that a pooled connection is only reused by requests which would connect the same way.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ConnectTarget {
+    resolved: Option<IpAddr>,
+    #[cfg(unix)]
+    unix_socket: Option<PathBuf>,
+    local_address: Option<IpAddr>,
+}
+
+/// Connects to the host of a URL (or its overridden address) over TCP, or to a Unix socket, and wraps the stream in TLS for HTTPS.
+pub(crate) struct Connector {
+    ssl: NativeTlsClient,
+    resolve: Vec<(String, IpAddr)>,
+    #[cfg(unix)]
+    unix_socket: Option<PathBuf>,
+    tls_handshake_timeout: Option<Duration>,
+    tcp_nodelay: bool,
+    tcp_keepalive: Option<Duration>,
+    local_address: Option<IpAddr>,
+    timings: Option<Arc<Mutex<ConnectionTimings>>>,
+    captured_stream: Option<CapturedStream>,
+    peer_addr: Arc<Mutex<Option<SocketAddr>>>,
+}
+
+impl Connector {
+    #[inline]
+    pub(crate) fn new(ssl: NativeTlsClient, options: &HttpRequestOptions) -> Connector {
+        Connector {
+            ssl,
+            resolve: options.resolve.clone(),
+            #[cfg(unix)]
+            unix_socket: options.unix_socket.clone(),
+            tls_handshake_timeout: options.tls_handshake_timeout,
+            tcp_nodelay: options.tcp_nodelay,
+            tcp_keepalive: options.tcp_keepalive,
+            local_address: options.local_address,
+            timings: if options.collect_timings {
+                Some(Arc::new(Mutex::new(ConnectionTimings::default())))
+            } else {
+                None
+            },
+            captured_stream: None,
+            peer_addr: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Keep a clone of the stream of the next connection, so that it can be used after hyper is done with it (e.g. after a 101 response).
+    #[inline]
+    pub(crate) fn capture_stream(&mut self) -> CapturedStream {
+        self.captured_stream.get_or_insert_with(Default::default).clone()
+    }
+
+    /// The timings of the last connection, if the `collect_timings` option is enabled.
+    #[inline]
+    pub(crate) fn timings(&self) -> Option<Arc<Mutex<ConnectionTimings>>> {
+        self.timings.clone()
+    }
+
+    /// The address of the other side of the last connection. A Unix socket has none.
+    #[inline]
+    pub(crate) fn peer_addr(&self) -> Arc<Mutex<Option<SocketAddr>>> {
+        self.peer_addr.clone()
+    }
+
+    #[inline]
+    pub(crate) fn record_peer_addr(&self, stream: &mut ConnectionStream) {
+        *self.peer_addr.lock().unwrap() = stream.peer_addr().ok();
+    }
+
+    /// The address overridden by the `resolve` option, the Unix socket and the local address which a connection to this host uses.
+    pub(crate) fn target(&self, host: &str) -> ConnectTarget {
+        ConnectTarget {
+            resolved: self.resolved_ip(host),
+            #[cfg(unix)]
+            unix_socket: self.unix_socket.clone(),
+            local_address: self.local_address,
+        }
+    }
+
+    #[inline]
+    fn resolved_ip(&self, host: &str) -> Option<IpAddr> {
+        self.resolve.iter().find(|(h, _)| h.eq_ignore_ascii_case(host)).map(|(_, ip)| *ip)
+    }
+
+    #[inline]
+    fn record<F: FnOnce(&mut ConnectionTimings)>(&self, f: F) {
+        if let Some(timings) = self.timings.as_ref() {
+            f(&mut timings.lock().unwrap());
+        }
+    }
+
+    fn connect_plain(&self, host: &str, port: u16) -> io::Result<PlainStream> {
+        #[cfg(unix)]
+        {
+            if let Some(path) = self.unix_socket.as_ref() {
+                let start_time = Instant::now();
+
+                let stream = UnixStream::connect(path)?;
+
+                self.record(|timings| timings.connect = start_time.elapsed());
+
+                return Ok(PlainStream::Unix(stream));
+            }
+        }
+
+        let addrs = match self.resolved_ip(host) {
+            Some(ip) => vec![SocketAddr::new(ip, port)],
+            None => {
+                // IPv6 hosts in URLs are enclosed in brackets
+                let host = host.trim_start_matches('[').trim_end_matches(']');
+
+                match host.parse::<IpAddr>() {
+                    Ok(ip) => vec![SocketAddr::new(ip, port)],
+                    Err(_) => {
+                        let start_time = Instant::now();
+
+                        let addrs = (host, port).to_socket_addrs()?.collect();
+
+                        self.record(|timings| timings.dns = Some(start_time.elapsed()));
+
+                        addrs
+                    }
+                }
+            }
+        };
+
+        let start_time = Instant::now();
+
+        let stream = match self.local_address {
+            Some(local_address) => connect_from(&addrs, local_address)?,
+            None => TcpStream::connect(&addrs[..])?,
+        };
+
+        self.record(|timings| timings.connect = start_time.elapsed());
+
+        if self.tcp_nodelay {
+            stream.set_nodelay(true)?;
+        }
+
+        if let Some(time) = self.tcp_keepalive {
+            SockRef::from(&stream).set_tcp_keepalive(&TcpKeepalive::new().with_time(time))?;
+        }
+
+        Ok(PlainStream::Tcp(HttpStream(stream)))
+    }
+
+    fn connect_stream(
+        &self,
+        host: &str,
+        port: u16,
+        scheme: &str,
+    ) -> hyper::Result<ConnectionStream> {
+        match scheme {
+            "http" => Ok(ConnectionStream::Http(self.connect_plain(host, port)?)),
+            "https" => {
+                let stream = self.connect_plain(host, port)?;
+
+                let start_time = Instant::now();
+
+                let stream = match self.tls_handshake_timeout {
+                    Some(timeout) => {
+                        stream.set_read_timeout(Some(timeout))?;
+                        stream.set_write_timeout(Some(timeout))?;
+
+                        let stream = self.ssl.wrap_client(stream, host).map_err(|err| {
+                            // the handshake error of a timed out socket does not keep the kind of the I/O error
+                            if start_time.elapsed() >= timeout {
+                                hyper::Error::Io(io::Error::new(
+                                    io::ErrorKind::TimedOut,
+                                    "The TLS handshake has timed out.",
+                                ))
+                            } else {
+                                err
+                            }
+                        })?;
+
+                        // hyper sets its own timeouts after connecting
+                        stream.set_read_timeout(None)?;
+                        stream.set_write_timeout(None)?;
+
+                        stream
+                    }
+                    None => self.ssl.wrap_client(stream, host)?,
+                };
+
+                self.record(|timings| timings.tls = Some(start_time.elapsed()));
+
+                Ok(ConnectionStream::Https(stream))
+            }
+            _ => {
+                Err(hyper::Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Invalid scheme for HTTP",
+                )))
+            }
+        }
+    }
+}
+
+impl NetworkConnector for Connector {
+    type Stream = ConnectionStream;
+
+    fn connect(&self, host: &str, port: u16, scheme: &str) -> hyper::Result<ConnectionStream> {
+        let mut stream = self.connect_stream(host, port, scheme)?;
+
+        self.record_peer_addr(&mut stream);
+
+        if let Some(captured_stream) = self.captured_stream.as_ref() {
+            *captured_stream.lock().unwrap() = Some(stream.clone());
+        }
+
+        Ok(stream)
+    }
+}
+
+/// Connect to the first address which accepts the connection, from a socket bound to the local address.
+fn connect_from(addrs: &[SocketAddr], local_address: IpAddr) -> io::Result<TcpStream> {
+    let mut last_err = None;
+
+    for addr in addrs.iter().filter(|addr| addr.is_ipv4() == local_address.is_ipv4()) {
+        let socket = Socket::new(Domain::for_address(*addr), Type::STREAM, Some(Protocol::TCP))?;
+
+        socket.bind(&SocketAddr::new(local_address, 0).into())?;
+
+        match socket.connect(&(*addr).into()) {
+            Ok(()) => return Ok(socket.into()),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "No address of the host is in the family of the local address.",
+        )
+    }))
+}
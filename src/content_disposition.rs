@@ -0,0 +1,110 @@
+/// Get the file name of a `Content-Disposition` header value. The `filename*` parameter (RFC 5987) takes precedence over `filename`. Only the last component of a path is kept, so that the name cannot point to another directory.
+pub(crate) fn parse_filename(content_disposition: &str) -> Option<String> {
+    let mut filename = None;
+    let mut extended_filename = None;
+
+    // the first item is the disposition type
+    for parameter in split_parameters(content_disposition).into_iter().skip(1) {
+        let index = match parameter.find('=') {
+            Some(index) => index,
+            None => continue,
+        };
+
+        let name = parameter[..index].trim();
+        let value = parameter[index + 1..].trim();
+
+        if name.eq_ignore_ascii_case("filename*") {
+            extended_filename = decode_extended_value(value);
+        } else if name.eq_ignore_ascii_case("filename") {
+            filename = Some(unquote(value));
+        }
+    }
+
+    let filename = extended_filename.or(filename)?;
+
+    let filename = filename.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+
+    if filename.is_empty() || filename == "." || filename == ".." {
+        None
+    } else {
+        Some(filename.to_string())
+    }
+}
+
+/// Split at semicolons which are not in quoted strings.
+fn split_parameters(s: &str) -> Vec<&str> {
+    let mut parameters = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (index, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                parameters.push(&s[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+
+    parameters.push(&s[start..]);
+
+    parameters
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+        Some(value) => {
+            let mut unquoted = String::with_capacity(value.len());
+            let mut chars = value.chars();
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => unquoted.extend(chars.next()),
+                    _ => unquoted.push(c),
+                }
+            }
+
+            unquoted
+        }
+        None => value.to_string(),
+    }
+}
+
+/// Decode `charset'language'percent-encoded-value`. Only UTF-8 and ISO-8859-1 are supported.
+fn decode_extended_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let encoded = parts.next()?.as_bytes();
+
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+
+    while index < encoded.len() {
+        if encoded[index] == b'%' {
+            let hex = encoded.get(index + 1..index + 3)?;
+
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+
+            index += 3;
+        } else {
+            bytes.push(encoded[index]);
+
+            index += 1;
+        }
+    }
+
+    if charset.eq_ignore_ascii_case("UTF-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("ISO-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::SystemTime;
+
+use crate::http_date::parse_http_date;
+
+/// A cookie set by a `Set-Cookie` response header. See `HttpResponse::cookies`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    /// The `Expires` attribute.
+    pub expires: Option<SystemTime>,
+    /// The `Max-Age` attribute, in seconds. It takes precedence over `expires`.
+    pub max_age: Option<i64>,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: Option<String>,
+}
+
+impl Cookie {
+    /// Parse the value of a single `Set-Cookie` header.
+    pub fn parse<S: AsRef<str>>(set_cookie: S) -> Option<Cookie> {
+        let mut attributes = set_cookie.as_ref().split(';');
+
+        let (name, value) = split_pair(attributes.next()?)?;
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim_matches('"').to_string(),
+            domain: None,
+            path: None,
+            expires: None,
+            max_age: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        };
+
+        for attribute in attributes {
+            let (name, value) = split_pair(attribute).unwrap_or((attribute.trim(), ""));
+
+            match name.to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    cookie.domain = Some(value.trim_start_matches('.').to_string())
+                }
+                "path" if !value.is_empty() => cookie.path = Some(value.to_string()),
+                "expires" => cookie.expires = parse_http_date(value),
+                "max-age" => cookie.max_age = value.parse().ok(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" if !value.is_empty() => cookie.same_site = Some(value.to_string()),
+                _ => (),
+            }
+        }
+
+        Some(cookie)
+    }
+
+    /// Parse `Set-Cookie` headers which have been joined by commas.
+    pub(crate) fn parse_joined(set_cookies: &str) -> Vec<Cookie> {
+        split_joined(set_cookies).into_iter().filter_map(Cookie::parse).collect()
+    }
+}
+
+#[inline]
+fn split_pair(s: &str) -> Option<(&str, &str)> {
+    let index = s.find('=')?;
+
+    Some((s[..index].trim(), s[index + 1..].trim()))
+}
+
+/// Split at commas which start a new cookie (`name=`), so that the commas in `Expires` dates are kept.
+fn split_joined(s: &str) -> Vec<&str> {
+    let mut cookies = Vec::new();
+    let mut start = 0;
+
+    for (index, _) in s.match_indices(',') {
+        let rest = &s[index + 1..];
+
+        let token_end = rest.find(['=', ';', ',']).unwrap_or(rest.len());
+
+        let starts_cookie = rest[token_end..].starts_with('=')
+            && !rest[..token_end].trim().is_empty()
+            && !rest[..token_end].trim().contains(' ');
+
+        if starts_cookie {
+            cookies.push(s[start..index].trim());
+            start = index + 1;
+        }
+    }
+
+    cookies.push(s[start..].trim());
+
+    cookies
+}
@@ -0,0 +1,44 @@
+use crate::HttpResponse;
+
+/// The `Access-Control-Allow-*` headers of a CORS preflight response. See `HttpRequest::preflight`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsPreflight {
+    pub status_code: u16,
+    /// `Access-Control-Allow-Origin`
+    pub allow_origin: Option<String>,
+    /// `Access-Control-Allow-Methods`
+    pub allow_methods: Vec<String>,
+    /// `Access-Control-Allow-Headers`
+    pub allow_headers: Vec<String>,
+    /// `Access-Control-Allow-Credentials`
+    pub allow_credentials: bool,
+    /// `Access-Control-Max-Age`, in seconds.
+    pub max_age: Option<u64>,
+}
+
+impl CorsPreflight {
+    /// Parse the `Access-Control-Allow-*` headers of a response.
+    pub fn from_response(response: &HttpResponse) -> CorsPreflight {
+        CorsPreflight {
+            status_code: response.status_code,
+            allow_origin: response
+                .header("Access-Control-Allow-Origin")
+                .map(|v| v.trim().to_string()),
+            allow_methods: split_list(response.header("Access-Control-Allow-Methods")),
+            allow_headers: split_list(response.header("Access-Control-Allow-Headers")),
+            allow_credentials: response
+                .header("Access-Control-Allow-Credentials")
+                .is_some_and(|v| v.trim().eq_ignore_ascii_case("true")),
+            max_age: response.header("Access-Control-Max-Age").and_then(|v| v.trim().parse().ok()),
+        }
+    }
+}
+
+fn split_list(value: Option<&str>) -> Vec<String> {
+    match value {
+        Some(value) => {
+            value.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()).map(String::from).collect()
+        }
+        None => Vec::new(),
+    }
+}
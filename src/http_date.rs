@@ -0,0 +1,169 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] =
+    ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MAX_YEAR: u64 = 9999;
+
+/// Parse an HTTP-date in any of the three formats of RFC 7231.
+///
+/// * `Sun, 06 Nov 1994 08:49:37 GMT` (IMF-fixdate)
+/// * `Sunday, 06-Nov-94 08:49:37 GMT` (RFC 850)
+/// * `Sun Nov  6 08:49:37 1994` (asctime)
+///
+/// An invalid date (e.g. `31 Feb`) or a year before 1970 or after 9999 is `None`.
+pub fn parse_http_date(s: &str) -> Option<SystemTime> {
+    let s = s.trim();
+
+    let (day, month, year, time) = match s.find(',') {
+        Some(index) => {
+            let mut parts = s[index + 1..].split_whitespace();
+
+            let date = parts.next()?;
+            let (day, month, year) = if date.contains('-') {
+                // RFC 850
+                let mut date = date.split('-');
+
+                let day = date.next()?;
+                let month = date.next()?;
+                let year: u64 = date.next()?.parse().ok()?;
+
+                // two-digit years which appear to be more than 50 years in the future are in the past
+                let year = if year < 100 {
+                    if year < 70 {
+                        year + 2000
+                    } else {
+                        year + 1900
+                    }
+                } else {
+                    year
+                };
+
+                (day, month, year)
+            } else {
+                // IMF-fixdate
+                let month = parts.next()?;
+                let year = parts.next()?.parse().ok()?;
+
+                (date, month, year)
+            };
+
+            let time = parts.next()?;
+
+            if !parts.next()?.eq_ignore_ascii_case("GMT") {
+                return None;
+            }
+
+            (day, month, year, time)
+        }
+        None => {
+            // asctime
+            let mut parts = s.split_whitespace();
+
+            parts.next()?;
+
+            let month = parts.next()?;
+            let day = parts.next()?;
+            let time = parts.next()?;
+            let year = parts.next()?.parse().ok()?;
+
+            (day, month, year, time)
+        }
+    };
+
+    let day: u64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| m.eq_ignore_ascii_case(month))? as u64 + 1;
+
+    let mut time = time.split(':');
+
+    let hour: u64 = time.next()?.parse().ok()?;
+    let minute: u64 = time.next()?.parse().ok()?;
+    let second: u64 = time.next()?.parse().ok()?;
+
+    if !(1970..=MAX_YEAR).contains(&year)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+
+    let secs = days.checked_mul(86400)?.checked_add(hour * 3600 + minute * 60 + second)?;
+
+    UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// Format a time as an IMF-fixdate (e.g. `Sun, 06 Nov 1994 08:49:37 GMT`), for headers such as `If-Modified-Since`. A time before 1970 is formatted as 1970-01-01.
+pub fn format_http_date(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+    let days = secs / 86400;
+    let secs = secs % 86400;
+
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        // 1970-01-01 is a Thursday
+        WEEKDAYS[((days + 4) % 7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// The count of days from 1970-01-01. See http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 {
+        year - 1
+    } else {
+        year
+    };
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+/// The year, month and day of a count of days from 1970-01-01. The inverse of `days_from_civil`.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 {
+        mp + 3
+    } else {
+        mp - 9
+    };
+    let year = yoe + era * 400;
+
+    if month <= 2 {
+        (year + 1, month, day)
+    } else {
+        (year, month, day)
+    }
+}
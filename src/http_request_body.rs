@@ -1,11 +1,21 @@
 use std::collections::HashMap;
+use std::fs;
 use std::hash::Hash;
+use std::io;
+use std::path::Path;
 
 use mime::Mime;
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+use crate::MultipartPart;
 
 /// A http request body that you want to send.
+///
+/// Every kind of body is held in memory, so it is sent again when a `307` or `308` redirection is followed. A `303` redirection drops it and switches to `GET`.
 #[derive(Debug)]
 pub enum HttpRequestBody<BK: Eq + Hash + AsRef<str>, BV: AsRef<str>> {
+    /// The bytes are sent as they are, so a pre-compressed body only needs a `Content-Encoding` header.
     Binary {
         content_type: Mime,
         body: Vec<u8>,
@@ -15,7 +25,45 @@ pub enum HttpRequestBody<BK: Eq + Hash + AsRef<str>, BV: AsRef<str>> {
         body: String,
     },
     FormURLEncoded(HashMap<BK, BV>),
-    // TODO Multi-part
+    /// Like `FormURLEncoded`, but the pairs are encoded in order and a name can be repeated.
+    FormURLEncodedOrdered(Vec<(BK, BV)>),
+    /// Bytes sent without a `Content-Type` header, so that the server can sniff it.
+    Raw(Vec<u8>),
+    /// A `multipart/form-data` body. See `MultipartBuilder`.
+    Multipart {
+        boundary: String,
+        parts: Vec<MultipartPart>,
+    },
+}
+
+impl<BK: Eq + Hash + AsRef<str>, BV: AsRef<str>> HttpRequestBody<BK, BV> {
+    /// Create a `Text` body of `application/json` from an already-serialized JSON string.
+    #[inline]
+    pub fn json_str<S: Into<String>>(s: S) -> Self {
+        HttpRequestBody::Text {
+            content_type: mime::APPLICATION_JSON,
+            body: s.into(),
+        }
+    }
+
+    /// Create a `Text` body of `application/json` serialized from `value`.
+    #[cfg(feature = "json")]
+    pub fn json<T: Serialize + ?Sized>(value: &T) -> Result<Self, serde_json::Error> {
+        Ok(HttpRequestBody::Text {
+            content_type: mime::APPLICATION_JSON,
+            body: serde_json::to_string(value)?,
+        })
+    }
+
+    /// Create a `Binary` body from the content of a file. The `Content-Length` header will be the size of the file.
+    pub fn from_file<P: AsRef<Path>>(path: P, content_type: Mime) -> Result<Self, io::Error> {
+        let body = fs::read(path)?;
+
+        Ok(HttpRequestBody::Binary {
+            content_type,
+            body,
+        })
+    }
 }
 
 impl<BK: Eq + Hash + AsRef<str> + Clone, BV: AsRef<str> + Clone> Clone for HttpRequestBody<BK, BV> {
@@ -48,6 +96,19 @@ impl<BK: Eq + Hash + AsRef<str> + Clone, BV: AsRef<str> + Clone> Clone for HttpR
 
                 HttpRequestBody::FormURLEncoded(new_map)
             }
+            HttpRequestBody::FormURLEncodedOrdered(pairs) => {
+                HttpRequestBody::FormURLEncodedOrdered(pairs.clone())
+            }
+            HttpRequestBody::Raw(body) => HttpRequestBody::Raw(body.clone()),
+            HttpRequestBody::Multipart {
+                boundary,
+                parts,
+            } => {
+                HttpRequestBody::Multipart {
+                    boundary: boundary.clone(),
+                    parts: parts.clone(),
+                }
+            }
         }
     }
 }
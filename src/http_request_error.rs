@@ -1,19 +1,61 @@
 use std::error::Error;
 use std::fmt::{Display, Error as FmtError, Formatter};
 use std::io::Error as IOError;
+use std::time::Duration;
 
 use hyper::error::{Error as HyperError, ParseError};
+use mime::Mime;
 
-/// Errors for `HttpRequest`.
+use crate::HttpResponse;
+
+/// Errors for `HttpRequest`. It is `Send + Sync + 'static`, so it can be boxed into `Box<dyn Error + Send + Sync>`.
 #[derive(Debug)]
 pub enum HttpRequestError {
     UrlParseError(ParseError),
     HyperError(HyperError),
     IOError(IOError),
-    RedirectError(&'static str),
-    TooLarge,
-    TimeOut,
+    /// A redirection cannot be followed. `url` is the `Location` header (or the URL resolved from it) and `status` is the status code of the redirection, if they are known.
+    RedirectError {
+        message: &'static str,
+        url: Option<String>,
+        status: Option<u16>,
+    },
+    /// `size` is the count of bytes which had been read when the request was aborted.
+    TooLarge {
+        size: usize,
+        limit: usize,
+    },
+    HeadersTooLarge,
+    /// The response has more header lines than `HttpRequestOptions::max_header_count`.
+    TooManyHeaders {
+        limit: usize,
+    },
+    /// The host already has `HttpSession::max_connections_per_host` requests in progress, and `HttpSession::error_on_connection_limit` is enabled.
+    TooManyConnections {
+        limit: usize,
+    },
+    /// The size of the received body does not match the `Content-Length` header.
+    IncompleteBody {
+        expected: usize,
+        received: usize,
+    },
+    /// The body to be deserialized is empty, e.g. of a `204 No Content` response. `status` is the status code of the response.
+    EmptyBody {
+        status: u16,
+    },
+    TimeOut(Duration),
+    /// The `cancel_token` of the options has been set.
+    Cancelled,
     LocalNotAllow,
+    /// The media type of the response is not the expected one. `found` is the `Content-Type` header, if any.
+    UnexpectedContentType {
+        expected: Mime,
+        found: Option<String>,
+    },
+    /// The server responded with a 4xx or 5xx status. See `HttpResponse::error_for_status`.
+    StatusError(Box<HttpResponse>),
+    #[cfg(feature = "json")]
+    JsonError(serde_json::Error),
     Other(&'static str),
 }
 
@@ -38,6 +80,14 @@ impl From<IOError> for HttpRequestError {
     }
 }
 
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for HttpRequestError {
+    #[inline]
+    fn from(error: serde_json::Error) -> Self {
+        HttpRequestError::JsonError(error)
+    }
+}
+
 impl Display for HttpRequestError {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
@@ -45,10 +95,87 @@ impl Display for HttpRequestError {
             HttpRequestError::UrlParseError(err) => Display::fmt(err, f),
             HttpRequestError::HyperError(err) => Display::fmt(err, f),
             HttpRequestError::IOError(err) => Display::fmt(err, f),
-            HttpRequestError::RedirectError(text) => f.write_str(text),
-            HttpRequestError::TooLarge => f.write_str("Remote data is too large."),
-            HttpRequestError::TimeOut => f.write_str("The connection has timed out."),
+            HttpRequestError::RedirectError {
+                message,
+                url,
+                status,
+            } => {
+                f.write_str(message)?;
+
+                if let Some(status) = status {
+                    f.write_fmt(format_args!(" (status: {})", status))?;
+                }
+
+                if let Some(url) = url {
+                    f.write_fmt(format_args!(" (location: {})", url))?;
+                }
+
+                Ok(())
+            }
+            HttpRequestError::TooLarge {
+                size,
+                limit,
+            } => {
+                f.write_fmt(format_args!(
+                "Remote data is too large. ({} bytes have been read, but the limit is {} bytes.)",
+                size, limit
+            ))
+            }
+            HttpRequestError::HeadersTooLarge => f.write_str("Remote headers are too large."),
+            HttpRequestError::TooManyHeaders {
+                limit,
+            } => f.write_fmt(format_args!("Remote headers are more than {}.", limit)),
+            HttpRequestError::TooManyConnections {
+                limit,
+            } => f.write_fmt(format_args!("The host already has {} requests in progress.", limit)),
+            HttpRequestError::IncompleteBody {
+                expected,
+                received,
+            } => {
+                f.write_fmt(format_args!(
+                "The body is incomplete. ({} bytes are expected, but {} bytes have been received.)",
+                expected, received
+            ))
+            }
+            HttpRequestError::EmptyBody {
+                status,
+            } => f.write_fmt(format_args!("The body is empty. (The status is {}.)", status)),
+            HttpRequestError::TimeOut(elapsed) => {
+                f.write_fmt(format_args!(
+                    "The connection has timed out. ({} ms elapsed.)",
+                    elapsed.as_millis()
+                ))
+            }
+            HttpRequestError::Cancelled => f.write_str("The request has been cancelled."),
             HttpRequestError::LocalNotAllow => f.write_str("Local addresses are not allowed."),
+            HttpRequestError::UnexpectedContentType {
+                expected,
+                found,
+            } => {
+                match found {
+                    Some(found) => {
+                        f.write_fmt(format_args!(
+                            "The content type is unexpected. ({} is expected, but {} is found.)",
+                            expected.essence_str(),
+                            found
+                        ))
+                    }
+                    None => {
+                        f.write_fmt(format_args!(
+                            "The content type is unexpected. ({} is expected, but none is found.)",
+                            expected.essence_str()
+                        ))
+                    }
+                }
+            }
+            HttpRequestError::StatusError(response) => {
+                f.write_fmt(format_args!(
+                    "The server responded with an error status. ({})",
+                    response.status_code
+                ))
+            }
+            #[cfg(feature = "json")]
+            HttpRequestError::JsonError(err) => Display::fmt(err, f),
             HttpRequestError::Other(text) => f.write_str(text),
         }
     }
@@ -14,6 +14,10 @@ pub enum HttpRequestMethod {
     DELETE,
     /// Only get the headers of resources.
     HEAD,
+    /// Get the communication options of resources, e.g. a CORS preflight request.
+    OPTIONS,
+    /// Establish a tunnel to the host and port of the URL.
+    CONNECT,
 }
 
 impl HttpRequestMethod {
@@ -25,6 +29,8 @@ impl HttpRequestMethod {
             HttpRequestMethod::PUT => "PUT",
             HttpRequestMethod::DELETE => "DELETE",
             HttpRequestMethod::HEAD => "HEAD",
+            HttpRequestMethod::OPTIONS => "OPTIONS",
+            HttpRequestMethod::CONNECT => "CONNECT",
         }
     }
 }
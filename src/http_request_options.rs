@@ -1,29 +1,205 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::IpAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use url::Url;
+
+#[cfg(feature = "body-hash")]
+use crate::BodyHashAlgorithm;
+use crate::{
+    ConnectionPool, HttpRequestOptionsBuilder, HttpResponseHead, RedirectAction,
+    RedirectMethodPolicy,
+};
+
 const DEFAULT_MAX_RESPONSE_BODY_SIZE: usize = 1024 * 1024;
+const DEFAULT_MAX_RESPONSE_HEADERS_SIZE: usize = 64 * 1024;
+const DEFAULT_MAX_RESPONSE_HEADER_COUNT: usize = 100;
 const DEFAULT_MAX_REDIRECT_COUNT: usize = 5;
 const DEFAULT_MAX_CONNECTION_TIME: u64 = 60000;
 const DEFAULT_ALLOW_LOCAL: bool = true;
+const DEFAULT_ALLOW_HTTPS_DOWNGRADE: bool = false;
+
+static GLOBAL_DEFAULT: OnceLock<HttpRequestOptions> = OnceLock::new();
+
+/// A closure which produces a fresh `Authorization` header value. See `HttpRequest::on_unauthorized`.
+pub type UnauthorizedHandler = Arc<Mutex<dyn FnMut() -> String + Send>>;
+
+/// A closure which decides what to do with a redirection, given the resolved `Location` URL, the status code and the 3xx response. See `HttpRequestOptions::redirect_handler`.
+pub type RedirectHandler =
+    Arc<Mutex<dyn FnMut(&Url, u16, &HttpResponseHead) -> RedirectAction + Send>>;
+
+/// A writer to which the requests are traced. See `HttpRequestOptions::trace_writer`.
+pub type TraceWriter = Arc<Mutex<dyn Write + Send>>;
 
 /// Options for `HttpRequest`.
-#[derive(Debug, Clone)]
+#[derive(Educe, Clone)]
+#[educe(Debug)]
 pub struct HttpRequestOptions {
-    /// The size limit in bytes of the response body. The default value is `1 * 1024 * 1024` (1 MiB).
-    pub max_response_body_size: usize,
+    /// The size limit in bytes of the (decompressed) response body. `None` means the size is unlimited. The bodies of followed redirections are not read at all (their connections are closed), so only the final response counts. The default value is `Some(1 * 1024 * 1024)` (1 MiB).
+    pub max_response_body_size: Option<usize>,
+    /// The size limit in bytes of the (decompressed) response body of a 4xx or 5xx response, instead of `max_response_body_size`. The default value is `None`, which means `max_response_body_size` is used.
+    pub max_error_body_size: Option<usize>,
+    /// The size limit in bytes of the response headers (names and values). The default value is `64 * 1024` (64 KiB).
+    pub max_response_headers_size: usize,
+    /// The count limit of the response header lines. The default value is `100`, which is also the most hyper can parse.
+    pub max_header_count: usize,
     /// The count limit of redirection times. The default value is `5`.
     pub max_redirect_count: usize,
+    /// The status codes whose redirections are followed, and how. A status code which is not in it is returned as the response. The default value has `301`, `302`, `307` and `308` to keep the method and `303` to switch to `GET`.
+    pub redirect_behavior: HashMap<u16, RedirectMethodPolicy>,
+    /// Decide whether to follow each redirection (possibly to another URL) instead of the built-in checks of `allow_https_downgrade` and `same_host_redirects_only`. The `https_only` and `max_redirect_count` options still apply. The default value is `None`.
+    #[educe(Debug(ignore))]
+    pub redirect_handler: Option<RedirectHandler>,
     /// The time limit in milliseconds of a connection. 0 means the time is unlimited. The default value is `60000` (1 minute).
     pub max_connection_time: u64,
-    /// Whether to allow to request local URL resources. The default value is `true`.
+    /// The absolute time limit of a request, including redirections. If `max_connection_time` is also set, whichever is reached first wins. The default value is `None`.
+    pub deadline: Option<Instant>,
+    /// Abort the request with `HttpRequestError::Cancelled` once this flag is set. It is checked before each connection and between reads of the body, so a blocking read is not interrupted. The default value is `None`.
+    pub cancel_token: Option<Arc<AtomicBool>>,
+    /// The time limit of the TLS handshake of an HTTPS connection, apart from `max_connection_time`. The default value is `None`.
+    pub tls_handshake_timeout: Option<Duration>,
+    /// Whether to set `TCP_NODELAY` on the socket, which disables Nagle's algorithm. The default value is `false`.
+    pub tcp_nodelay: bool,
+    /// Enable TCP keepalive on the socket, sending probes after the connection has been idle for this long. The default value is `None`, which keeps the setting of the system.
+    pub tcp_keepalive: Option<Duration>,
+    /// Bind the socket to this local address before connecting, so that the request goes out of the interface which has it, like the `--interface` option of curl. Only the addresses of the host in the same family are tried. The default value is `None`.
+    pub local_address: Option<IpAddr>,
+    /// The time limit of waiting for data between two successive reads of the response, apart from `max_connection_time`. The default value is `None`.
+    pub idle_read_timeout: Option<Duration>,
+    /// Whether to allow to request local URL resources. The default value is `true`. Set it to `false` (or use `HttpRequest::get_safe`) if the URL comes from untrusted input.
     pub allow_local: bool,
+    /// Whether to allow to follow a redirection from an HTTPS URL to an HTTP URL. The default value is `false`.
+    pub allow_https_downgrade: bool,
+    /// Whether to only follow redirections to the same host (the port may differ). The default value is `false`.
+    pub same_host_redirects_only: bool,
+    /// Whether to only request HTTPS URLs. A non-HTTPS URL is rejected, and a redirection to a non-HTTPS URL is not followed (the 3xx response is returned instead). The default value is `false`.
+    pub https_only: bool,
+    /// Whether to return the response with the partial body (marked as `truncated`) instead of an error when reading the body fails. The default value is `false`.
+    pub return_partial_on_error: bool,
+    /// Whether to return `HttpRequestError::IncompleteBody` if the size of the received body does not match the `Content-Length` header. The default value is `false`.
+    pub validate_content_length: bool,
+    /// Whether to decompress a response body without a `Content-Encoding` header if it starts with the magic bytes of gzip or zlib (deflate). Only enable it for servers which are known to omit the header, because a binary body can start with the same bytes. The default value is `false`.
+    pub sniff_compression: bool,
+    /// Whether to also return the response headers with their original names and order as `HttpResponse::raw_headers`. The default value is `false`.
+    pub preserve_raw_headers: bool,
+    /// The names of response headers which keep their original casing as the keys of `HttpResponse::headers`, for code which needs to see exactly how a server cased them (e.g. `SOAPAction`). The other names are lowercased. `header` lookups stay case-insensitive. The default value is empty.
+    pub case_preserved_headers: Vec<String>,
+    /// Whether to measure how long each phase of the request takes and return it as `HttpResponse::timings`. The default value is `false`.
+    pub collect_timings: bool,
+    /// Connect to the paired IP address instead of resolving the host, like the `--resolve` option of curl. The `Host` header and the TLS SNI keep using the original host. The default value is empty.
+    pub resolve: Vec<(String, IpAddr)>,
+    /// Send this value as the `Host` header while still connecting to the host of the URL (and using it for the TLS SNI). It is also applied to redirections which stay on the host of the original URL, but not to other hosts. The default value is `None`.
+    pub host_override: Option<String>,
+    /// Whether to send `Connection: close` (unless the `Connection` header has been set) so that the server does not keep the connection alive. The default value is `false`.
+    pub disable_keep_alive: bool,
+    /// Whether to send `DEFAULT_USER_AGENT` as the `User-Agent` header if it has not been set. If it is `false` and no `User-Agent` header has been set, the request has none. The default value is `true`.
+    pub send_default_user_agent: bool,
+    /// Reuse the idle keep-alive connections of this pool, and put connections back into it. Requests sent through a proxy, with `Expect: 100-continue` or with an `Upgrade` header do not use it. The default value is `None`, which means every request has a connection of its own.
+    pub connection_pool: Option<ConnectionPool>,
+    /// Whether to send a request which has a body with `Expect: 100-continue`, and send the body only after the server responds with `100 Continue` (or does not respond within a second, or half of the read timeout if it is shorter), so that a rejected upload is not sent. It does not apply to requests sent through a proxy. The default value is `false`.
+    pub use_expect_continue: bool,
+    /// Whether to skip interim (1xx) responses such as `103 Early Hints` and return the final response after them. `101 Switching Protocols` is always final. It does not apply to requests sent through a proxy. The default value is `true`.
+    pub skip_interim_responses: bool,
+    /// The URL of an HTTP proxy (e.g. `http://127.0.0.1:3128`) to send requests through. HTTPS requests are tunneled with `CONNECT`. The default value is `None`.
+    pub proxy: Option<String>,
+    /// The hosts which are connected to directly instead of through the proxy, like the `NO_PROXY` environment variable. An entry can be an exact host, a domain suffix starting with a dot (e.g. `.internal`), or `*` for all hosts. The default value is empty.
+    pub no_proxy: Vec<String>,
+    /// Invoked on a 401 response to produce a fresh `Authorization` header value, with which the request is retried once. The default value is `None`.
+    #[educe(Debug(ignore))]
+    pub on_unauthorized: Option<UnauthorizedHandler>,
+    /// Write the method, the URL, the headers and a summary of the body of every request (including redirections) to this writer before sending it, like `curl -v`. The default value is `None`.
+    #[educe(Debug(ignore))]
+    pub trace_writer: Option<TraceWriter>,
+    /// Connect to this Unix socket instead of the host of the URL, which is then only used for the `Host` header. The default value is `None`.
+    #[cfg(unix)]
+    pub unix_socket: Option<PathBuf>,
+    /// Hash the (decompressed) body with this algorithm while it is being read, into `HttpResponse::body_hash`. The default value is `None`.
+    #[cfg(feature = "body-hash")]
+    pub body_hash_algorithm: Option<BodyHashAlgorithm>,
 }
 
 impl Default for HttpRequestOptions {
     #[inline]
     fn default() -> Self {
         HttpRequestOptions {
-            max_response_body_size: DEFAULT_MAX_RESPONSE_BODY_SIZE,
+            max_response_body_size: Some(DEFAULT_MAX_RESPONSE_BODY_SIZE),
+            max_error_body_size: None,
+            max_response_headers_size: DEFAULT_MAX_RESPONSE_HEADERS_SIZE,
+            max_header_count: DEFAULT_MAX_RESPONSE_HEADER_COUNT,
             max_redirect_count: DEFAULT_MAX_REDIRECT_COUNT,
+            redirect_behavior: default_redirect_behavior(),
+            redirect_handler: None,
             max_connection_time: DEFAULT_MAX_CONNECTION_TIME,
+            deadline: None,
+            cancel_token: None,
+            tls_handshake_timeout: None,
+            tcp_nodelay: false,
+            tcp_keepalive: None,
+            local_address: None,
+            idle_read_timeout: None,
             allow_local: DEFAULT_ALLOW_LOCAL,
+            allow_https_downgrade: DEFAULT_ALLOW_HTTPS_DOWNGRADE,
+            same_host_redirects_only: false,
+            https_only: false,
+            return_partial_on_error: false,
+            validate_content_length: false,
+            sniff_compression: false,
+            preserve_raw_headers: false,
+            case_preserved_headers: Vec::new(),
+            collect_timings: false,
+            resolve: Vec::new(),
+            host_override: None,
+            disable_keep_alive: false,
+            send_default_user_agent: true,
+            connection_pool: None,
+            use_expect_continue: false,
+            skip_interim_responses: true,
+            proxy: None,
+            no_proxy: Vec::new(),
+            on_unauthorized: None,
+            trace_writer: None,
+            #[cfg(unix)]
+            unix_socket: None,
+            #[cfg(feature = "body-hash")]
+            body_hash_algorithm: None,
         }
     }
 }
+
+#[inline]
+fn default_redirect_behavior() -> HashMap<u16, RedirectMethodPolicy> {
+    let mut redirect_behavior = HashMap::with_capacity(5);
+
+    for status_code in [301, 302, 307, 308] {
+        redirect_behavior.insert(status_code, RedirectMethodPolicy::Keep);
+    }
+
+    redirect_behavior.insert(303, RedirectMethodPolicy::Get);
+
+    redirect_behavior
+}
+
+impl HttpRequestOptions {
+    /// Create a builder whose initial options are the default ones.
+    #[inline]
+    pub fn builder() -> HttpRequestOptionsBuilder {
+        HttpRequestOptionsBuilder::new()
+    }
+
+    /// Set the options which new requests start with, instead of the default ones, for the whole process. It can only be set once, so `false` is returned if it has already been set.
+    #[inline]
+    pub fn set_global_default(options: HttpRequestOptions) -> bool {
+        GLOBAL_DEFAULT.set(options).is_ok()
+    }
+
+    /// The options which new requests start with: the ones set by `set_global_default`, or the default ones.
+    #[inline]
+    pub fn global_default() -> HttpRequestOptions {
+        GLOBAL_DEFAULT.get().cloned().unwrap_or_default()
+    }
+}
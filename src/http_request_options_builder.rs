@@ -0,0 +1,303 @@
+use std::convert::TryFrom;
+use std::env;
+use std::io::Write;
+use std::net::IpAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use url::Url;
+
+#[cfg(feature = "body-hash")]
+use crate::BodyHashAlgorithm;
+use crate::{
+    ConnectionPool, HttpRequestOptions, HttpResponseHead, RedirectAction, RedirectMethodPolicy,
+};
+
+/// A builder of `HttpRequestOptions`. The setters are named after the fields of `HttpRequestOptions`.
+#[derive(Debug, Clone, Default)]
+pub struct HttpRequestOptionsBuilder {
+    options: HttpRequestOptions,
+}
+
+impl HttpRequestOptionsBuilder {
+    #[inline]
+    pub fn new() -> HttpRequestOptionsBuilder {
+        HttpRequestOptionsBuilder::default()
+    }
+
+    #[inline]
+    pub fn max_response_body_size(mut self, max_response_body_size: Option<usize>) -> Self {
+        self.options.max_response_body_size = max_response_body_size;
+        self
+    }
+
+    #[inline]
+    pub fn max_error_body_size(mut self, max_error_body_size: Option<usize>) -> Self {
+        self.options.max_error_body_size = max_error_body_size;
+        self
+    }
+
+    #[inline]
+    pub fn max_response_headers_size(mut self, max_response_headers_size: usize) -> Self {
+        self.options.max_response_headers_size = max_response_headers_size;
+        self
+    }
+
+    #[inline]
+    pub fn max_header_count(mut self, max_header_count: usize) -> Self {
+        self.options.max_header_count = max_header_count;
+        self
+    }
+
+    #[inline]
+    pub fn max_redirect_count(mut self, max_redirect_count: usize) -> Self {
+        self.options.max_redirect_count = max_redirect_count;
+        self
+    }
+
+    /// Set how the redirections of a status code are followed, or stop following them with `None`.
+    #[inline]
+    pub fn redirect_behavior(
+        mut self,
+        status_code: u16,
+        policy: Option<RedirectMethodPolicy>,
+    ) -> Self {
+        match policy {
+            Some(policy) => self.options.redirect_behavior.insert(status_code, policy),
+            None => self.options.redirect_behavior.remove(&status_code),
+        };
+        self
+    }
+
+    #[inline]
+    pub fn redirect_handler<
+        F: FnMut(&Url, u16, &HttpResponseHead) -> RedirectAction + Send + 'static,
+    >(
+        mut self,
+        f: F,
+    ) -> Self {
+        self.options.redirect_handler = Some(Arc::new(Mutex::new(f)));
+        self
+    }
+
+    #[inline]
+    pub fn max_connection_time(mut self, max_connection_time: u64) -> Self {
+        self.options.max_connection_time = max_connection_time;
+        self
+    }
+
+    /// Set `max_connection_time` with a `Duration`. It is rounded up to whole milliseconds, so that a short duration does not become `0` (unlimited).
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.max_connection_time =
+            u64::try_from(timeout.as_nanos().div_ceil(1_000_000)).unwrap_or(u64::MAX);
+        self
+    }
+
+    #[inline]
+    pub fn deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.options.deadline = deadline;
+        self
+    }
+
+    #[inline]
+    pub fn cancel_token(mut self, cancel_token: Option<Arc<AtomicBool>>) -> Self {
+        self.options.cancel_token = cancel_token;
+        self
+    }
+
+    #[inline]
+    pub fn tls_handshake_timeout(mut self, tls_handshake_timeout: Option<Duration>) -> Self {
+        self.options.tls_handshake_timeout = tls_handshake_timeout;
+        self
+    }
+
+    #[inline]
+    pub fn tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.options.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    #[inline]
+    pub fn tcp_keepalive(mut self, tcp_keepalive: Option<Duration>) -> Self {
+        self.options.tcp_keepalive = tcp_keepalive;
+        self
+    }
+
+    #[inline]
+    pub fn local_address(mut self, local_address: Option<IpAddr>) -> Self {
+        self.options.local_address = local_address;
+        self
+    }
+
+    #[inline]
+    pub fn idle_read_timeout(mut self, idle_read_timeout: Option<Duration>) -> Self {
+        self.options.idle_read_timeout = idle_read_timeout;
+        self
+    }
+
+    #[inline]
+    pub fn allow_local(mut self, allow_local: bool) -> Self {
+        self.options.allow_local = allow_local;
+        self
+    }
+
+    #[inline]
+    pub fn allow_https_downgrade(mut self, allow_https_downgrade: bool) -> Self {
+        self.options.allow_https_downgrade = allow_https_downgrade;
+        self
+    }
+
+    #[inline]
+    pub fn same_host_redirects_only(mut self, same_host_redirects_only: bool) -> Self {
+        self.options.same_host_redirects_only = same_host_redirects_only;
+        self
+    }
+
+    #[inline]
+    pub fn https_only(mut self, https_only: bool) -> Self {
+        self.options.https_only = https_only;
+        self
+    }
+
+    #[inline]
+    pub fn return_partial_on_error(mut self, return_partial_on_error: bool) -> Self {
+        self.options.return_partial_on_error = return_partial_on_error;
+        self
+    }
+
+    #[inline]
+    pub fn validate_content_length(mut self, validate_content_length: bool) -> Self {
+        self.options.validate_content_length = validate_content_length;
+        self
+    }
+
+    #[inline]
+    pub fn sniff_compression(mut self, sniff_compression: bool) -> Self {
+        self.options.sniff_compression = sniff_compression;
+        self
+    }
+
+    #[inline]
+    pub fn preserve_raw_headers(mut self, preserve_raw_headers: bool) -> Self {
+        self.options.preserve_raw_headers = preserve_raw_headers;
+        self
+    }
+
+    #[inline]
+    pub fn case_preserved_headers(mut self, case_preserved_headers: Vec<String>) -> Self {
+        self.options.case_preserved_headers = case_preserved_headers;
+        self
+    }
+
+    #[inline]
+    pub fn collect_timings(mut self, collect_timings: bool) -> Self {
+        self.options.collect_timings = collect_timings;
+        self
+    }
+
+    /// Add a host and the IP address to connect to instead of resolving the host.
+    #[inline]
+    pub fn resolve<S: Into<String>>(mut self, host: S, ip: IpAddr) -> Self {
+        self.options.resolve.push((host.into(), ip));
+        self
+    }
+
+    #[inline]
+    pub fn host_override(mut self, host_override: Option<String>) -> Self {
+        self.options.host_override = host_override;
+        self
+    }
+
+    #[inline]
+    pub fn disable_keep_alive(mut self, disable_keep_alive: bool) -> Self {
+        self.options.disable_keep_alive = disable_keep_alive;
+        self
+    }
+
+    #[inline]
+    pub fn send_default_user_agent(mut self, send_default_user_agent: bool) -> Self {
+        self.options.send_default_user_agent = send_default_user_agent;
+        self
+    }
+
+    #[inline]
+    pub fn connection_pool(mut self, connection_pool: Option<ConnectionPool>) -> Self {
+        self.options.connection_pool = connection_pool;
+        self
+    }
+
+    #[inline]
+    pub fn use_expect_continue(mut self, use_expect_continue: bool) -> Self {
+        self.options.use_expect_continue = use_expect_continue;
+        self
+    }
+
+    #[inline]
+    pub fn skip_interim_responses(mut self, skip_interim_responses: bool) -> Self {
+        self.options.skip_interim_responses = skip_interim_responses;
+        self
+    }
+
+    #[inline]
+    pub fn proxy(mut self, proxy: Option<String>) -> Self {
+        self.options.proxy = proxy;
+        self
+    }
+
+    /// Add a host (or a domain suffix starting with a dot, or `*`) which bypasses the proxy.
+    #[inline]
+    pub fn no_proxy<S: Into<String>>(mut self, host: S) -> Self {
+        self.options.no_proxy.push(host.into());
+        self
+    }
+
+    /// Add the comma-separated hosts of the `NO_PROXY` (or `no_proxy`) environment variable.
+    pub fn no_proxy_from_env(mut self) -> Self {
+        if let Ok(no_proxy) = env::var("NO_PROXY").or_else(|_| env::var("no_proxy")) {
+            self.options.no_proxy.extend(
+                no_proxy
+                    .split(',')
+                    .map(|host| host.trim())
+                    .filter(|host| !host.is_empty())
+                    .map(String::from),
+            );
+        }
+
+        self
+    }
+
+    #[inline]
+    pub fn on_unauthorized<F: FnMut() -> String + Send + 'static>(mut self, f: F) -> Self {
+        self.options.on_unauthorized = Some(Arc::new(Mutex::new(f)));
+        self
+    }
+
+    #[inline]
+    pub fn trace_writer<W: Write + Send + 'static>(mut self, writer: W) -> Self {
+        self.options.trace_writer = Some(Arc::new(Mutex::new(writer)));
+        self
+    }
+
+    #[cfg(unix)]
+    #[inline]
+    pub fn unix_socket(mut self, unix_socket: Option<PathBuf>) -> Self {
+        self.options.unix_socket = unix_socket;
+        self
+    }
+
+    #[cfg(feature = "body-hash")]
+    #[inline]
+    pub fn body_hash_algorithm(mut self, body_hash_algorithm: Option<BodyHashAlgorithm>) -> Self {
+        self.options.body_hash_algorithm = body_hash_algorithm;
+        self
+    }
+
+    #[inline]
+    pub fn build(self) -> HttpRequestOptions {
+        self.options
+    }
+}
@@ -0,0 +1,79 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+
+use hyper::header::Headers;
+use url::Url;
+
+use crate::HttpRequestMethod;
+
+/// The parts of a http request which are about to be sent. See `HttpRequest::build_request_parts`.
+#[derive(Debug, Clone)]
+pub struct HttpRequestParts<'a> {
+    pub method: HttpRequestMethod,
+    /// The URL including the query.
+    pub url: Url,
+    /// The headers, including the default `User-Agent`, `Content-Type` and `Content-Length`.
+    pub headers: Headers,
+    pub body: Option<Cow<'a, [u8]>>,
+}
+
+impl HttpRequestParts<'_> {
+    /// Render these parts as a `curl` command line for POSIX shells. A body which is not UTF-8 is piped into curl by `printf`.
+    pub fn to_curl(&self) -> String {
+        let mut command = String::new();
+
+        let binary_body = self.body.as_deref().filter(|body| std::str::from_utf8(body).is_err());
+
+        if let Some(body) = binary_body {
+            // POSIX `printf` understands octal escapes, but not `\x` ones
+            command.push_str("printf '");
+
+            for b in body.iter() {
+                command.write_fmt(format_args!("\\{:03o}", b)).unwrap();
+            }
+
+            command.push_str("' | ");
+        }
+
+        command.push_str("curl");
+
+        match self.method {
+            // `-X HEAD` would make curl wait for a body
+            HttpRequestMethod::HEAD => command.push_str(" --head"),
+            method => {
+                command.push_str(" -X ");
+                command.push_str(method.get_str());
+            }
+        }
+
+        for header in self.headers.iter() {
+            // curl computes the length by itself
+            if header.name().eq_ignore_ascii_case("Content-Length") {
+                continue;
+            }
+
+            command.push_str(" -H ");
+            push_quoted(&mut command, &format!("{}: {}", header.name(), header.value_string()));
+        }
+
+        if binary_body.is_some() {
+            command.push_str(" --data-binary @-");
+        } else if let Some(body) = self.body.as_ref() {
+            // the body is UTF-8
+            command.push_str(" --data-raw ");
+            push_quoted(&mut command, std::str::from_utf8(body).unwrap());
+        }
+
+        command.push(' ');
+        push_quoted(&mut command, self.url.as_str());
+
+        command
+    }
+}
+
+/// Quote a string for POSIX shells with single quotes.
+fn push_quoted(command: &mut String, s: &str) {
+    command.push('\'');
+    command.push_str(&s.replace('\'', "'\\''"));
+    command.push('\'');
+}
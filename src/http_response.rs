@@ -1,9 +1,250 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::{self, Utf8Error};
+use std::time::SystemTime;
+
+use hyper::status::StatusCode;
+use mime::Mime;
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
+use url::{ParseError, Url};
+
+use crate::content_disposition::parse_filename;
+use crate::http_date::parse_http_date;
+use crate::{
+    check_redirect, find_header, Cookie, DefaultHttpRequest, HttpRequestError, HttpRequestMethod,
+    HttpRequestOptions, RedirectMethodPolicy, Timings,
+};
+
+const BODY_PREVIEW_SIZE: usize = 1024;
 
 /// The http response.
 #[derive(Debug)]
 pub struct HttpResponse {
     pub status_code: u16,
+    /// The header names are lowercased (except the `case_preserved_headers` ones), and the values of repeated headers are joined by `, `.
     pub headers: HashMap<String, String>,
+    /// The headers with their original names, in the order in which each name first appears. Repeated headers are kept as separate entries next to each other. Only available if the `preserve_raw_headers` option is enabled.
+    pub raw_headers: Option<Vec<(String, String)>>,
     pub body: Vec<u8>,
+    /// Whether this response comes from a redirected request.
+    pub was_redirected: bool,
+    /// The URL of the request which this response answers, which is the last one if redirected.
+    pub url: Url,
+    /// The method of the original request. It may differ from the method of the last request if redirected.
+    pub request_method: HttpRequestMethod,
+    /// The URL of the original request, including its query.
+    pub request_url: Url,
+    /// The address of the server (or the proxy) which the response comes from. It is `None` for a Unix socket.
+    pub remote_addr: Option<SocketAddr>,
+    /// Whether the body is incomplete because reading it failed. Only possible if the `return_partial_on_error` option is enabled.
+    pub truncated: bool,
+    /// The total size of the request bodies which have been sent, including the ones sent again for redirections and for a retry after a 401 response.
+    pub request_body_size: usize,
+    /// The size of the (decompressed) body which has been read, which is the length of `body`.
+    pub response_body_size: usize,
+    /// The lowercase hexadecimal digest of the (decompressed) body. Only available if the `body_hash_algorithm` option is set.
+    #[cfg(feature = "body-hash")]
+    pub body_hash: Option<String>,
+    /// How long each phase of the request took. Only available if the `collect_timings` option is enabled.
+    pub timings: Option<Timings>,
+}
+
+impl HttpResponse {
+    /// Get the value of a header. The name is case-insensitive.
+    #[inline]
+    pub fn header<S: AsRef<str>>(&self, name: S) -> Option<&str> {
+        find_header(&self.headers, name.as_ref())
+    }
+
+    /// Borrow the body as a string slice if it is valid UTF-8.
+    #[inline]
+    pub fn body_str(&self) -> Result<&str, Utf8Error> {
+        str::from_utf8(&self.body)
+    }
+
+    /// Borrow the body as a string, replacing invalid UTF-8 sequences with `U+FFFD`. It only allocates if there are invalid sequences.
+    #[inline]
+    pub fn body_str_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    /// Take the body, dropping the rest of the response. The accessors such as `body_str` and `content_type_mime` borrow the response, so they can be used before it.
+    #[inline]
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+
+    /// Deserialize the body as JSON. An empty body is `HttpRequestError::EmptyBody`.
+    #[cfg(feature = "json")]
+    #[inline]
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, HttpRequestError> {
+        if self.body.is_empty() {
+            return Err(HttpRequestError::EmptyBody {
+                status: self.status_code,
+            });
+        }
+
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Deserialize the body as JSON, or get `None` if the body is empty (e.g. of a `204 No Content` response).
+    #[cfg(feature = "json")]
+    #[inline]
+    pub fn json_opt<T: DeserializeOwned>(&self) -> Result<Option<T>, HttpRequestError> {
+        if self.body.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_slice(&self.body)?))
+    }
+
+    /// Write the body to a file, creating it or truncating it.
+    #[inline]
+    pub fn save_body_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        fs::write(path, &self.body)
+    }
+
+    /// Parse the `Content-Type` header, including its parameters such as `charset`. An invalid value is `None`.
+    #[inline]
+    pub fn content_type_mime(&self) -> Option<Mime> {
+        self.header("Content-Type")?.trim().parse().ok()
+    }
+
+    /// Turn a 4xx or 5xx response into a `StatusError`.
+    #[inline]
+    pub fn error_for_status(self) -> Result<HttpResponse, HttpRequestError> {
+        if self.status_code >= 400 {
+            Err(HttpRequestError::StatusError(Box::new(self)))
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Check that the type and the subtype of the `Content-Type` header are the ones of `expected`, ignoring the parameters.
+    pub fn expect_content_type(&self, expected: &Mime) -> Result<&Self, HttpRequestError> {
+        match self.content_type_mime() {
+            Some(content_type) if content_type.essence_str() == expected.essence_str() => Ok(self),
+            _ => {
+                Err(HttpRequestError::UnexpectedContentType {
+                    expected: expected.clone(),
+                    found: self.header("Content-Type").map(String::from),
+                })
+            }
+        }
+    }
+
+    /// Parse the `Date` header. An invalid or out-of-range date is `None`.
+    #[inline]
+    pub fn date(&self) -> Option<SystemTime> {
+        parse_http_date(self.header("Date")?)
+    }
+
+    /// Get the URL in the `Location` header, resolved against the URL of the request.
+    #[inline]
+    pub fn location(&self) -> Option<Result<Url, ParseError>> {
+        self.header("Location").map(|location| self.url.join(location.trim()))
+    }
+
+    /// Send the next request of a redirection, checked like an automatic one. Only one request is sent. The body is not sent again, so keeping a method other than `GET` or `HEAD` is an error.
+    pub fn follow(&self, options: &HttpRequestOptions) -> Result<HttpResponse, HttpRequestError> {
+        let redirect_error = |message, url| {
+            HttpRequestError::RedirectError {
+                message,
+                url,
+                status: Some(self.status_code),
+            }
+        };
+
+        let method =
+            match options.redirect_behavior.get(&self.status_code) {
+                Some(RedirectMethodPolicy::Keep) => {
+                    match self.request_method {
+                        HttpRequestMethod::GET | HttpRequestMethod::HEAD => self.request_method,
+                        _ => return Err(redirect_error(
+                            "The redirection keeps the method, but the body cannot be sent again.",
+                            None,
+                        )),
+                    }
+                }
+                Some(RedirectMethodPolicy::Get) => HttpRequestMethod::GET,
+                None => return Err(redirect_error("The status code is not a redirection.", None)),
+            };
+
+        let location_url = match self.location() {
+            Some(Ok(location_url)) => location_url,
+            Some(Err(_)) => {
+                return Err(redirect_error(
+                    "Cannot parse the `location` field in headers.",
+                    self.header("Location").map(String::from),
+                ));
+            }
+            None => {
+                return Err(redirect_error("Cannot get the `location` field in headers.", None))
+            }
+        };
+
+        check_redirect(&self.url, &location_url, self.status_code, options)?;
+
+        let mut request = DefaultHttpRequest::new(method, location_url);
+
+        request.options = options.clone();
+        // the caller follows the redirections
+        request.options.max_redirect_count = 0;
+
+        request.send()
+    }
+
+    /// Get the file name suggested by the `Content-Disposition` header, from its `filename*` (RFC 5987) or `filename` parameter. Directories in the name are removed.
+    #[inline]
+    pub fn suggested_filename(&self) -> Option<String> {
+        parse_filename(self.header("Content-Disposition")?)
+    }
+
+    /// Parse all `Set-Cookie` headers.
+    #[inline]
+    pub fn cookies(&self) -> Vec<Cookie> {
+        match self.header("Set-Cookie") {
+            Some(set_cookies) => Cookie::parse_joined(set_cookies),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Display for HttpResponse {
+    /// Render the status line, the sorted headers and a preview of the body (the first 1024 bytes, decoded lossily).
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.write_fmt(format_args!("{}", self.status_code))?;
+
+        if let Some(reason) = StatusCode::from_u16(self.status_code).canonical_reason() {
+            f.write_fmt(format_args!(" {}", reason))?;
+        }
+
+        f.write_str("\n")?;
+
+        let mut headers: Vec<(&String, &String)> = self.headers.iter().collect();
+
+        headers.sort();
+
+        for (name, value) in headers {
+            f.write_fmt(format_args!("{}: {}\n", name, value))?;
+        }
+
+        if !self.body.is_empty() {
+            let preview_size = self.body.len().min(BODY_PREVIEW_SIZE);
+
+            f.write_fmt(format_args!("\n{}", String::from_utf8_lossy(&self.body[..preview_size])))?;
+
+            if self.body.len() > preview_size {
+                f.write_fmt(format_args!("\n... ({} more bytes)", self.body.len() - preview_size))?;
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use url::Url;
+
+use crate::find_header;
+
+/// The status and the headers of a http response whose body has not been read. See `HttpRequest::send_streaming`.
+#[derive(Debug, Clone)]
+pub struct HttpResponseHead {
+    pub status_code: u16,
+    /// The header names are lowercased (except the `case_preserved_headers` ones), and the values of repeated headers are joined by `, `.
+    pub headers: HashMap<String, String>,
+    /// The headers with their original names. Only available if the `preserve_raw_headers` option is enabled.
+    pub raw_headers: Option<Vec<(String, String)>>,
+    /// Whether this response comes from a redirected request.
+    pub was_redirected: bool,
+    /// The URL of the request which this response answers, which is the last one if redirected.
+    pub url: Url,
+    /// The address of the server (or the proxy) which the response comes from. It is `None` for a Unix socket.
+    pub remote_addr: Option<SocketAddr>,
+}
+
+impl HttpResponseHead {
+    /// Get the value of a header. The name is case-insensitive.
+    #[inline]
+    pub fn header<S: AsRef<str>>(&self, name: S) -> Option<&str> {
+        find_header(&self.headers, name.as_ref())
+    }
+}
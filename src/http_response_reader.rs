@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Cursor, Read};
+use std::mem;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Instant;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+use crate::reader::CountingReader;
+use crate::{
+    check_cancelled, check_timeout, find_header, is_timeout_error, HttpRequestError,
+    HttpRequestOptions,
+};
+
+type Source = CountingReader<Box<dyn Read + Send>>;
+
+enum BodyDecoder {
+    Identity(Source),
+    /// The content coding is to be detected from the first bytes.
+    Sniff(Source),
+    Gzip(GzDecoder<Source>),
+    Deflate(ZlibDecoder<Source>),
+}
+
+impl BodyDecoder {
+    #[inline]
+    fn source(&self) -> &Source {
+        match self {
+            BodyDecoder::Identity(source) | BodyDecoder::Sniff(source) => source,
+            BodyDecoder::Gzip(decoder) => decoder.get_ref(),
+            BodyDecoder::Deflate(decoder) => decoder.get_ref(),
+        }
+    }
+
+    /// Read the first two bytes of a `Sniff` decoder and replace it with the decoder they indicate.
+    fn sniff(&mut self) -> io::Result<()> {
+        let empty = BodyDecoder::Identity(CountingReader::new(Box::new(io::empty())));
+
+        let mut source = match mem::replace(self, empty) {
+            BodyDecoder::Sniff(source) => source,
+            decoder => {
+                *self = decoder;
+
+                return Ok(());
+            }
+        };
+
+        let mut magic = [0u8; 2];
+        let mut length = 0;
+
+        let result = loop {
+            match source.read(&mut magic[length..]) {
+                Ok(0) => break Ok(()),
+                Ok(c) => {
+                    length += c;
+
+                    if length == magic.len() {
+                        break Ok(());
+                    }
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => break Err(err),
+            }
+        };
+
+        // the magic bytes are read again from the new source, so they are not counted twice
+        let source = CountingReader::new(Box::new(
+            Cursor::new(magic[..length].to_vec()).chain(source.into_inner()),
+        ) as Box<dyn Read + Send>);
+
+        if let Err(err) = result {
+            *self = BodyDecoder::Sniff(source);
+
+            return Err(err);
+        }
+
+        *self = match magic[..length] {
+            [0x1f, 0x8b] => BodyDecoder::Gzip(GzDecoder::new(source)),
+            // the compression method is deflate with a window of at most 32 KiB, and the check bits are valid
+            [cmf, flg]
+                if cmf & 0x0f == 8
+                    && cmf >> 4 <= 7
+                    && (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0 =>
+            {
+                BodyDecoder::Deflate(ZlibDecoder::new(source))
+            }
+            _ => BodyDecoder::Identity(source),
+        };
+
+        Ok(())
+    }
+}
+
+impl Read for BodyDecoder {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let BodyDecoder::Sniff(_) = self {
+            self.sniff()?;
+        }
+
+        let result = match self {
+            BodyDecoder::Identity(source) | BodyDecoder::Sniff(source) => return source.read(buf),
+            BodyDecoder::Gzip(decoder) => decoder.read(buf),
+            BodyDecoder::Deflate(decoder) => decoder.read(buf),
+        };
+
+        match result {
+            // an empty body is not a truncated stream, even if it has a `Content-Encoding` header
+            Err(_) if self.source().count() == 0 && self.source().is_eof() => Ok(0),
+            result => result,
+        }
+    }
+}
+
+/// The (decompressed) body of a http response, read from the still open connection. The size and time limits of the options are enforced while reading. See `HttpRequest::send_streaming`.
+///
+/// The errors of the limits are `HttpRequestError`s wrapped in `io::Error`s of the `Other` kind.
+pub struct HttpResponseReader {
+    decoder: BodyDecoder,
+    expected_size: Option<usize>,
+    size: usize,
+    max_size: Option<usize>,
+    start_time: Instant,
+    max_connection_time: u64,
+    deadline: Option<Instant>,
+    cancel_token: Option<Arc<AtomicBool>>,
+}
+
+impl HttpResponseReader {
+    pub(crate) fn new(
+        source: Box<dyn Read + Send>,
+        status_code: u16,
+        headers: &HashMap<String, String>,
+        has_body: bool,
+        options: &HttpRequestOptions,
+        start_time: Instant,
+    ) -> HttpResponseReader {
+        // the count of bytes received before decompression, to be compared with the Content-Length
+        let source = CountingReader::new(source);
+
+        // hyper has already decoded the transfer coding (e.g. chunked), so only the content coding is left
+        let decoder = match find_header(headers, "content-encoding") {
+            // e.g. the response to a HEAD request, which has the headers of the body it would have
+            _ if !has_body => BodyDecoder::Identity(source),
+            Some(encoding) => {
+                match encoding.trim().to_lowercase().as_str() {
+                    "gzip" | "x-gzip" => BodyDecoder::Gzip(GzDecoder::new(source)),
+                    "deflate" => BodyDecoder::Deflate(ZlibDecoder::new(source)),
+                    _ => BodyDecoder::Identity(source),
+                }
+            }
+            None if options.sniff_compression => BodyDecoder::Sniff(source),
+            None => BodyDecoder::Identity(source),
+        };
+
+        let expected_size = if has_body && options.validate_content_length {
+            find_header(headers, "content-length").and_then(|v| v.trim().parse().ok())
+        } else {
+            None
+        };
+
+        let max_size = match options.max_error_body_size {
+            Some(max_error_body_size) if status_code >= 400 => Some(max_error_body_size),
+            _ => options.max_response_body_size,
+        };
+
+        HttpResponseReader {
+            decoder,
+            expected_size,
+            size: 0,
+            max_size,
+            start_time,
+            max_connection_time: options.max_connection_time,
+            deadline: options.deadline,
+            cancel_token: options.cancel_token.clone(),
+        }
+    }
+
+    /// The count of (decompressed) bytes which have been read.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub(crate) fn read_inner(&mut self, buf: &mut [u8]) -> Result<usize, HttpRequestError> {
+        check_cancelled(&self.cancel_token)?;
+
+        let c = match self.decoder.read(buf) {
+            Ok(c) => c,
+            Err(err) if is_timeout_error(&err) => {
+                return Err(HttpRequestError::TimeOut(self.start_time.elapsed()));
+            }
+            Err(err) => return Err(self.incomplete_body_error().unwrap_or_else(|| err.into())),
+        };
+
+        if c == 0 {
+            if let Some(err) = self.incomplete_body_error() {
+                return Err(err);
+            }
+
+            return Ok(0);
+        }
+
+        self.size += c;
+
+        if let Some(max_size) = self.max_size {
+            if self.size > max_size {
+                return Err(HttpRequestError::TooLarge {
+                    size: self.size,
+                    limit: max_size,
+                });
+            }
+        }
+
+        check_timeout(self.start_time, self.max_connection_time, self.deadline)?;
+
+        Ok(c)
+    }
+
+    fn incomplete_body_error(&self) -> Option<HttpRequestError> {
+        let expected = self.expected_size?;
+        let received = self.decoder.source().count();
+
+        if received != expected {
+            Some(HttpRequestError::IncompleteBody {
+                expected,
+                received,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Read for HttpResponseReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_inner(buf).map_err(|err| {
+            match err {
+                HttpRequestError::IOError(err) => err,
+                _ => io::Error::other(err),
+            }
+        })
+    }
+}
+
+impl Debug for HttpResponseReader {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("HttpResponseReader")
+            .field("size", &self.size)
+            .field("max_size", &self.max_size)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,174 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Condvar, Mutex};
+
+use url::Url;
+
+use crate::{
+    to_string_extra_headers, to_string_headers, ConnectionPool, HttpRequest, HttpRequestError,
+    HttpResponse,
+};
+
+/// A session which applies its default headers to every request sent through it, and reuses keep-alive connections across them.
+#[derive(Debug, Clone, Default)]
+pub struct HttpSession {
+    /// The headers merged into the headers of every request. The headers of a request take precedence over them.
+    pub headers: HashMap<String, String>,
+    /// The pool used by the requests whose `connection_pool` option is not set.
+    pub connection_pool: ConnectionPool,
+    /// The count limit of the requests which are in progress at the same time for a scheme, host and port. Redirections are counted against the host of the original request. The default value is `None`, which means the count is unlimited.
+    pub max_connections_per_host: Option<usize>,
+    /// Whether to return `HttpRequestError::TooManyConnections` instead of waiting when the `max_connections_per_host` limit is reached. The default value is `false`.
+    pub error_on_connection_limit: bool,
+    in_progress: Arc<(Mutex<HashMap<HostKey, usize>>, Condvar)>,
+}
+
+type HostKey = (String, String, u16);
+
+impl HttpSession {
+    #[inline]
+    pub fn new() -> HttpSession {
+        HttpSession::default()
+    }
+
+    /// Send a request with the default headers of this session and drop the request.
+    #[inline]
+    pub fn send<QK, QV, BK, BV, HK, HV>(
+        &self,
+        request: HttpRequest<QK, QV, BK, BV, HK, HV>,
+    ) -> Result<HttpResponse, HttpRequestError>
+    where
+        QK: Eq + Hash + AsRef<str>,
+        QV: AsRef<str>,
+        BK: Eq + Hash + AsRef<str>,
+        BV: AsRef<str>,
+        HK: Eq + Hash + AsRef<str>,
+        HV: AsRef<str>, {
+        self.send_preserved(&request)
+    }
+
+    /// Send a request with the default headers of this session and preserve the request so that it can be used again.
+    pub fn send_preserved<QK, QV, BK, BV, HK, HV>(
+        &self,
+        request: &HttpRequest<QK, QV, BK, BV, HK, HV>,
+    ) -> Result<HttpResponse, HttpRequestError>
+    where
+        QK: Eq + Hash + AsRef<str>,
+        QV: AsRef<str>,
+        BK: Eq + Hash + AsRef<str>,
+        BV: AsRef<str>,
+        HK: Eq + Hash + AsRef<str>,
+        HV: AsRef<str>, {
+        let _permit = self.acquire(&request.url)?;
+
+        let extra_headers = to_string_extra_headers(&request.extra_headers);
+        let headers = self.merge_headers(&request.headers, &extra_headers);
+
+        let options = if request.options.connection_pool.is_some() {
+            Cow::Borrowed(&request.options)
+        } else {
+            let mut options = request.options.clone();
+
+            options.connection_pool = Some(self.connection_pool.clone());
+
+            Cow::Owned(options)
+        };
+
+        HttpRequest::<QK, QV, BK, BV, String, String>::send_request_inner(
+            request.method,
+            request.url.clone(),
+            &request.query,
+            &request.body,
+            &headers,
+            &Some(extra_headers),
+            &options,
+            0,
+        )
+    }
+
+    /// Wait for (or fail to get) a permit to send a request to the host of the URL, if the `max_connections_per_host` limit is set.
+    fn acquire(&self, url: &Url) -> Result<Option<Permit<'_>>, HttpRequestError> {
+        let limit = match self.max_connections_per_host {
+            Some(limit) => limit,
+            None => return Ok(None),
+        };
+
+        let key = (
+            url.scheme().to_string(),
+            url.host_str().unwrap_or_default().to_ascii_lowercase(),
+            url.port_or_known_default().unwrap_or_default(),
+        );
+
+        let (in_progress, released) = &*self.in_progress;
+
+        let mut in_progress = in_progress.lock().unwrap();
+
+        while in_progress.get(&key).copied().unwrap_or(0) >= limit {
+            if self.error_on_connection_limit {
+                return Err(HttpRequestError::TooManyConnections {
+                    limit,
+                });
+            }
+
+            in_progress = released.wait(in_progress).unwrap();
+        }
+
+        *in_progress.entry(key.clone()).or_insert(0) += 1;
+
+        Ok(Some(Permit {
+            session: self,
+            key,
+        }))
+    }
+
+    fn merge_headers<HK: Eq + Hash + AsRef<str>, HV: AsRef<str>>(
+        &self,
+        headers: &Option<HashMap<HK, HV>>,
+        extra_headers: &[(String, String)],
+    ) -> Option<HashMap<String, String>> {
+        let mut merged = to_string_headers(headers);
+
+        for (k, v) in self.headers.iter() {
+            // header names are case-insensitive
+            if !merged
+                .keys()
+                .chain(extra_headers.iter().map(|(name, _)| name))
+                .any(|name| name.eq_ignore_ascii_case(k))
+            {
+                merged.insert(k.clone(), v.clone());
+            }
+        }
+
+        if merged.is_empty() {
+            None
+        } else {
+            Some(merged)
+        }
+    }
+}
+
+/// Counts a request as in progress until it is dropped.
+struct Permit<'a> {
+    session: &'a HttpSession,
+    key: HostKey,
+}
+
+impl Drop for Permit<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        let (in_progress, released) = &*self.session.in_progress;
+
+        let mut in_progress = in_progress.lock().unwrap();
+
+        if let Some(count) = in_progress.get_mut(&self.key) {
+            *count -= 1;
+
+            if *count == 0 {
+                in_progress.remove(&self.key);
+            }
+        }
+
+        released.notify_all();
+    }
+}
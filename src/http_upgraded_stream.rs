@@ -0,0 +1,70 @@
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Read, Write};
+
+use crate::connector::ConnectionStream;
+use crate::HttpResponseReader;
+
+/// The connection of a `101 Switching Protocols` response, for the caller to speak the new protocol on. See `HttpRequest::send_upgrade`.
+///
+/// Bytes which the server sent right after the response headers are read first. Through a proxy, they can be lost.
+pub struct HttpUpgradedStream {
+    stream: ConnectionStream,
+    buffer: Vec<u8>,
+    buffer_start: usize,
+    // hyper closes the connection when its response is dropped, so it is kept until this stream is dropped
+    _reader: HttpResponseReader,
+}
+
+impl HttpUpgradedStream {
+    #[inline]
+    pub(crate) fn new(
+        stream: ConnectionStream,
+        buffer: Vec<u8>,
+        reader: HttpResponseReader,
+    ) -> HttpUpgradedStream {
+        HttpUpgradedStream {
+            stream,
+            buffer,
+            buffer_start: 0,
+            _reader: reader,
+        }
+    }
+}
+
+impl Read for HttpUpgradedStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buffer_start < self.buffer.len() {
+            let buffer = &self.buffer[self.buffer_start..];
+
+            let c = buffer.len().min(buf.len());
+
+            buf[..c].copy_from_slice(&buffer[..c]);
+
+            self.buffer_start += c;
+
+            return Ok(c);
+        }
+
+        self.stream.read(buf)
+    }
+}
+
+impl Write for HttpUpgradedStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Debug for HttpUpgradedStream {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("HttpUpgradedStream").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,219 @@
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::time::{Duration, Instant};
+
+use hyper::net::{NetworkConnector, NetworkStream};
+
+use crate::{check_timeout, HttpRequestError, HttpRequestOptions};
+
+/// The maximum count of interim responses before a final response.
+pub(crate) const MAX_INTERIM_RESPONSES: usize = 32;
+
+/// The limits of reading the interim responses of one request.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct InterimResponseLimits {
+    max_headers_size: usize,
+    start_time: Instant,
+    max_connection_time: u64,
+    deadline: Option<Instant>,
+}
+
+impl InterimResponseLimits {
+    #[inline]
+    pub(crate) fn new(options: &HttpRequestOptions, start_time: Instant) -> InterimResponseLimits {
+        InterimResponseLimits {
+            max_headers_size: options.max_response_headers_size,
+            start_time,
+            max_connection_time: options.max_connection_time,
+            deadline: options.deadline,
+        }
+    }
+}
+
+/// Makes the streams of a connector skip interim (1xx) responses, except `101 Switching Protocols`, because hyper takes the first response as the final one. See `HttpRequestOptions::skip_interim_responses`.
+pub(crate) struct InterimResponseFilter<C> {
+    pub(crate) connector: C,
+    pub(crate) enabled: bool,
+    pub(crate) limits: InterimResponseLimits,
+}
+
+impl<C: NetworkConnector> NetworkConnector for InterimResponseFilter<C>
+where
+    C::Stream: NetworkStream,
+{
+    type Stream = InterimResponseFilterStream<C::Stream>;
+
+    #[inline]
+    fn connect(&self, host: &str, port: u16, scheme: &str) -> hyper::Result<Self::Stream> {
+        Ok(InterimResponseFilterStream {
+            stream: self.connector.connect(host, port, scheme)?,
+            enabled: self.enabled,
+            limits: self.limits,
+            at_response_start: false,
+            pending: Vec::new(),
+            pending_start: 0,
+        })
+    }
+}
+
+pub(crate) struct InterimResponseFilterStream<S> {
+    stream: S,
+    enabled: bool,
+    limits: InterimResponseLimits,
+    /// Whether a request has been written and its response has not started yet.
+    at_response_start: bool,
+    /// The bytes which have been read while looking for the status line.
+    pending: Vec<u8>,
+    pending_start: usize,
+}
+
+impl<S: Read> InterimResponseFilterStream<S> {
+    /// Read until the status line of a final response, dropping the interim responses before it.
+    fn skip_interim_responses(&mut self) -> Result<(), HttpRequestError> {
+        let mut buffer = [0u8; 1024];
+        // how many bytes of the current interim response have been searched for the end of its headers
+        let mut searched: usize = 0;
+        let mut count = 0;
+
+        loop {
+            let limits = &self.limits;
+
+            check_timeout(limits.start_time, limits.max_connection_time, limits.deadline)?;
+
+            let pending = &self.pending[self.pending_start..];
+
+            match is_interim_response(pending) {
+                Some(false) => {
+                    self.at_response_start = false;
+
+                    return Ok(());
+                }
+                Some(true) => {
+                    // the end may have been split between two reads
+                    let from = searched.saturating_sub(3);
+
+                    if let Some(index) = pending[from..].windows(4).position(|w| w == b"\r\n\r\n") {
+                        self.pending_start += from + index + 4;
+
+                        searched = 0;
+                        count += 1;
+
+                        if count > MAX_INTERIM_RESPONSES {
+                            return Err(HttpRequestError::Other(
+                                "The server has sent too many interim responses.",
+                            ));
+                        }
+
+                        continue;
+                    }
+
+                    if pending.len() > limits.max_headers_size {
+                        return Err(HttpRequestError::HeadersTooLarge);
+                    }
+
+                    searched = pending.len();
+                }
+                None => (),
+            }
+
+            let c = self.stream.read(&mut buffer)?;
+
+            if c == 0 {
+                self.at_response_start = false;
+
+                return Ok(());
+            }
+
+            self.pending.drain(..self.pending_start);
+            self.pending_start = 0;
+            self.pending.extend_from_slice(&buffer[..c]);
+        }
+    }
+}
+
+/// Whether the bytes start with the status line of an interim response other than `101`, or `None` if more bytes are needed to tell.
+fn is_interim_response(bytes: &[u8]) -> Option<bool> {
+    const PREFIX: &[u8] = b"HTTP/1.";
+
+    let n = bytes.len().min(PREFIX.len());
+
+    if bytes[..n] != PREFIX[..n] {
+        return Some(false);
+    }
+
+    // e.g. `HTTP/1.1 103`
+    if bytes.len() < 12 {
+        return None;
+    }
+
+    Some(bytes[8] == b' ' && bytes[9] == b'1' && &bytes[9..12] != b"101")
+}
+
+impl<S: Read> Read for InterimResponseFilterStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.at_response_start {
+            self.skip_interim_responses().map_err(|err| {
+                match err {
+                    HttpRequestError::IOError(err) => err,
+                    _ => io::Error::other(err),
+                }
+            })?;
+        }
+
+        if self.pending_start < self.pending.len() {
+            let pending = &self.pending[self.pending_start..];
+
+            let c = pending.len().min(buf.len());
+
+            buf[..c].copy_from_slice(&pending[..c]);
+
+            self.pending_start += c;
+
+            if self.pending_start == self.pending.len() {
+                self.pending.clear();
+                self.pending_start = 0;
+            }
+
+            return Ok(c);
+        }
+
+        self.stream.read(buf)
+    }
+}
+
+impl<S: Write> Write for InterimResponseFilterStream<S> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // a (kept-alive) connection is written to only before its next response
+        self.at_response_start = self.enabled;
+
+        self.stream.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<S: NetworkStream> NetworkStream for InterimResponseFilterStream<S> {
+    #[inline]
+    fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    #[inline]
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.stream.set_read_timeout(dur)
+    }
+
+    #[inline]
+    fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.stream.set_write_timeout(dur)
+    }
+
+    #[inline]
+    fn close(&mut self, how: Shutdown) -> io::Result<()> {
+        self.stream.close(how)
+    }
+}
@@ -23,43 +23,113 @@ More examples are in the `examples` directory.
 pub extern crate hyper;
 pub extern crate hyper_native_tls;
 pub extern crate mime;
+#[cfg(feature = "json")]
+pub extern crate serde_json;
 pub extern crate slash_formatter;
 pub extern crate url;
 
 #[macro_use]
 extern crate educe;
 
+#[cfg(feature = "body-hash")]
+mod body_hash_algorithm;
+mod connection_pool;
+mod connector;
+mod content_disposition;
+mod cookie;
+mod cors_preflight;
+mod http_date;
 mod http_request_body;
 mod http_request_error;
 mod http_request_method;
 mod http_request_options;
+mod http_request_options_builder;
+mod http_request_parts;
 mod http_response;
-
+mod http_response_head;
+mod http_response_reader;
+mod http_session;
+mod http_upgraded_stream;
+mod interim_response_filter;
+mod multipart_builder;
+mod multipart_part;
+mod reader;
+mod redirect_action;
+mod redirect_method_policy;
+#[cfg(feature = "async")]
+mod send_future;
+mod sse_event;
+mod sse_event_stream;
+mod timings;
+
+#[cfg(feature = "body-hash")]
+pub use body_hash_algorithm::BodyHashAlgorithm;
+pub use connection_pool::ConnectionPool;
+pub use cookie::Cookie;
+pub use cors_preflight::CorsPreflight;
+pub use http_date::{format_http_date, parse_http_date};
 pub use http_request_body::HttpRequestBody;
 pub use http_request_error::HttpRequestError;
 pub use http_request_method::HttpRequestMethod;
-pub use http_request_options::HttpRequestOptions;
+pub use http_request_options::{
+    HttpRequestOptions, RedirectHandler, TraceWriter, UnauthorizedHandler,
+};
+pub use http_request_options_builder::HttpRequestOptionsBuilder;
+pub use http_request_parts::HttpRequestParts;
 pub use http_response::HttpResponse;
-
+pub use http_response_head::HttpResponseHead;
+pub use http_response_reader::HttpResponseReader;
+pub use http_session::HttpSession;
+pub use http_upgraded_stream::HttpUpgradedStream;
+pub use multipart_builder::MultipartBuilder;
+pub use multipart_part::MultipartPart;
+pub use redirect_action::RedirectAction;
+pub use redirect_method_policy::RedirectMethodPolicy;
+#[cfg(feature = "async")]
+pub use send_future::SendFuture;
+pub use sse_event::SseEvent;
+pub use sse_event_stream::SseEventStream;
+pub use timings::Timings;
+
+use std::borrow::Cow;
 use std::cmp::Eq;
 use std::collections::HashMap;
-use std::fmt::Write;
 use std::hash::Hash;
-use std::io::Read;
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::io::{self, BufRead, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use url::{Host, Url};
-
-use hyper::client::{Body, Client, RedirectPolicy, RequestBuilder};
-use hyper::header::Headers;
+use mime::Mime;
+#[cfg(feature = "json")]
+use serde::Serialize;
+use url::{form_urlencoded, Host, Position, Url};
+
+#[cfg(feature = "body-hash")]
+use body_hash_algorithm::BodyHasher;
+use connection_pool::PooledConnector;
+use connector::{ConnectionStream, Connector};
+use interim_response_filter::{
+    InterimResponseFilter, InterimResponseLimits, MAX_INTERIM_RESPONSES,
+};
+use timings::ConnectionTimings;
+
+use hyper::buffer::BufReader;
+use hyper::client::{Body, Client, ProxyConfig, RedirectPolicy, RequestBuilder};
+use hyper::error::Error as HyperError;
+use hyper::header::{ContentLength, Headers};
+use hyper::http::h1::{parse_response, HttpReader};
 use hyper::method::Method;
-use hyper::net::HttpsConnector;
+use hyper::net::{NetworkConnector, NetworkStream};
 use hyper_native_tls::NativeTlsClient;
 
 const BUFFER_SIZE: usize = 512;
-const DEFAULT_USER_AGENT: &str =
+/// How long to wait for a `100 Continue` response before sending the body anyway.
+const EXPECT_CONTINUE_TIMEOUT: Duration = Duration::from_secs(1);
+/// The `User-Agent` header which is sent unless the request sets one (or the `send_default_user_agent` option is disabled).
+pub const DEFAULT_USER_AGENT: &str =
     concat!("Mozilla/5.0 (Rust; magiclen.org) EasyHyperRequest/", env!("CARGO_PKG_VERSION"));
 
 /// Use strings for query, body and headers.
@@ -82,12 +152,54 @@ where
     HV: AsRef<str>, {
     pub method: HttpRequestMethod,
     pub url: Url,
+    /// The pairs are appended to the query of `url` as `application/x-www-form-urlencoded`, in which spaces are encoded as `+`.
     pub query: Option<HashMap<QK, QV>>,
+    /// Without a body, a request other than `GET` and `HEAD` is still sent with `Content-Length: 0`. A body is also sent with `GET` and `DELETE`, which is non-standard but required by some APIs (e.g. Elasticsearch), except for a `GET` request through a proxy.
     pub body: Option<HttpRequestBody<BK, BV>>,
+    /// The `Host` header is derived from the host and port of the URL (an IP literal included) unless it is set here, or by the `host_override` option, which takes precedence. A `Host` header set here is kept for redirections which stay on the host of the URL, and dropped for redirections to other hosts.
     pub headers: Option<HashMap<HK, HV>>,
+    /// Headers which are appended after `headers`, so that a header can have multiple values.
+    pub extra_headers: Option<Vec<(HK, HV)>>,
     pub options: HttpRequestOptions,
 }
 
+impl<
+        QK: Eq + Hash + AsRef<str>,
+        QV: AsRef<str>,
+        BK: Eq + Hash + AsRef<str>,
+        BV: AsRef<str>,
+        HK: Eq + Hash + AsRef<str> + From<&'static str>,
+        HV: AsRef<str>,
+    > HttpRequest<QK, QV, BK, BV, HK, HV>
+{
+    /// Set the `Idempotency-Key` header, so that the server can recognize a retried request (e.g. a payment) and not perform it twice. The header name type only needs to be creatable from a `&'static str`, as `String`, `&'static str` and `Cow<'static, str>` are.
+    #[inline]
+    pub fn idempotency_key<S: Into<HV>>(mut self, key: S) -> Self {
+        self.set_header(HK::from("Idempotency-Key"), key.into());
+
+        self
+    }
+}
+
+#[cfg(feature = "async")]
+impl<
+        QK: Eq + Hash + AsRef<str> + Send + 'static,
+        QV: AsRef<str> + Send + 'static,
+        BK: Eq + Hash + AsRef<str> + Send + 'static,
+        BV: AsRef<str> + Send + 'static,
+        HK: Eq + Hash + AsRef<str> + Send + 'static,
+        HV: AsRef<str> + Send + 'static,
+    > HttpRequest<QK, QV, BK, BV, HK, HV>
+{
+    /// Send a request on a thread of its own and return a future of the response, for async code. The future works with any executor.
+    ///
+    /// The request is still a blocking one. Every call spawns a new OS thread, and dropping the future does not cancel the request (use `HttpRequestOptions::cancel_token` for that). If the request panics, the future resolves to `HttpRequestError::Other`.
+    #[inline]
+    pub fn send_async(self) -> SendFuture {
+        SendFuture::spawn(move || self.send())
+    }
+}
+
 impl<
         QK: Eq + Hash + AsRef<str>,
         QV: AsRef<str>,
@@ -104,10 +216,48 @@ impl<
             query: None,
             body: None,
             headers: None,
-            options: HttpRequestOptions::default(),
+            extra_headers: None,
+            options: HttpRequestOptions::global_default(),
         }
     }
 
+    /// Create a request from the components of its URL. The url crate can only construct a `Url` by parsing, so only `scheme://host` is parsed and the other components are set on it.
+    pub fn from_parts<S: AsRef<str>, H: AsRef<str>, P: AsRef<str>>(
+        method: HttpRequestMethod,
+        scheme: S,
+        host: H,
+        port: Option<u16>,
+        path: P,
+        query: Option<&str>,
+    ) -> Result<HttpRequest<QK, QV, BK, BV, HK, HV>, HttpRequestError> {
+        let scheme = scheme.as_ref();
+
+        if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+            return Err(HttpRequestError::Other("The scheme needs to be HTTP or HTTPS."));
+        }
+
+        let host = host.as_ref();
+
+        // the port is given separately, so only IPv6 hosts (in brackets) can contain colons
+        if host.is_empty()
+            || host.contains(['/', '?', '#', '@', '\\'])
+            || (!host.starts_with('[') && host.contains(':'))
+        {
+            return Err(HttpRequestError::Other("The host is invalid."));
+        }
+
+        let mut url = Url::parse(&format!("{}://{}", scheme, host))?;
+
+        if url.set_port(port).is_err() {
+            return Err(HttpRequestError::Other("The port is invalid."));
+        }
+
+        url.set_path(path.as_ref());
+        url.set_query(query);
+
+        Ok(Self::new(method, url))
+    }
+
     pub fn get(url: Url) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
         Self::new(HttpRequestMethod::GET, url)
     }
@@ -120,6 +270,23 @@ impl<
         Ok(Self::get(url))
     }
 
+    /// Create a `GET` request which is not allowed to request local URL resources. Use this when the URL comes from untrusted input.
+    pub fn get_safe(url: Url) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
+        let mut request = Self::get(url);
+
+        request.options.allow_local = false;
+
+        request
+    }
+
+    pub fn get_safe_from_url_str<S: AsRef<str>>(
+        url: S,
+    ) -> Result<HttpRequest<QK, QV, BK, BV, HK, HV>, HttpRequestError> {
+        let url = Url::parse(url.as_ref())?;
+
+        Ok(Self::get_safe(url))
+    }
+
     pub fn post(url: Url) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
         Self::new(HttpRequestMethod::POST, url)
     }
@@ -132,6 +299,44 @@ impl<
         Ok(Self::post(url))
     }
 
+    /// Create a `POST` request with a `FormURLEncoded` body.
+    pub fn post_form(url: Url, form: HashMap<BK, BV>) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
+        let mut request = Self::post(url);
+
+        request.body = Some(HttpRequestBody::FormURLEncoded(form));
+
+        request
+    }
+
+    /// Create a `POST` request with a `Text` body.
+    pub fn post_text<S: Into<String>>(
+        url: Url,
+        content_type: Mime,
+        text: S,
+    ) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
+        let mut request = Self::post(url);
+
+        request.body = Some(HttpRequestBody::Text {
+            content_type,
+            body: text.into(),
+        });
+
+        request
+    }
+
+    /// Create a `POST` request with a JSON body serialized from `value`.
+    #[cfg(feature = "json")]
+    pub fn post_json<T: Serialize + ?Sized>(
+        url: Url,
+        value: &T,
+    ) -> Result<HttpRequest<QK, QV, BK, BV, HK, HV>, HttpRequestError> {
+        let mut request = Self::post(url);
+
+        request.body = Some(HttpRequestBody::json(value)?);
+
+        Ok(request)
+    }
+
     pub fn put(url: Url) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
         Self::new(HttpRequestMethod::PUT, url)
     }
@@ -144,6 +349,44 @@ impl<
         Ok(Self::put(url))
     }
 
+    /// Create a `PUT` request with a `FormURLEncoded` body.
+    pub fn put_form(url: Url, form: HashMap<BK, BV>) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
+        let mut request = Self::put(url);
+
+        request.body = Some(HttpRequestBody::FormURLEncoded(form));
+
+        request
+    }
+
+    /// Create a `PUT` request with a `Text` body.
+    pub fn put_text<S: Into<String>>(
+        url: Url,
+        content_type: Mime,
+        text: S,
+    ) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
+        let mut request = Self::put(url);
+
+        request.body = Some(HttpRequestBody::Text {
+            content_type,
+            body: text.into(),
+        });
+
+        request
+    }
+
+    /// Create a `PUT` request with a JSON body serialized from `value`.
+    #[cfg(feature = "json")]
+    pub fn put_json<T: Serialize + ?Sized>(
+        url: Url,
+        value: &T,
+    ) -> Result<HttpRequest<QK, QV, BK, BV, HK, HV>, HttpRequestError> {
+        let mut request = Self::put(url);
+
+        request.body = Some(HttpRequestBody::json(value)?);
+
+        Ok(request)
+    }
+
     pub fn delete(url: Url) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
         Self::new(HttpRequestMethod::DELETE, url)
     }
@@ -156,6 +399,28 @@ impl<
         Ok(Self::delete(url))
     }
 
+    /// Create a `DELETE` request with a `FormURLEncoded` body.
+    pub fn delete_form(url: Url, form: HashMap<BK, BV>) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
+        let mut request = Self::delete(url);
+
+        request.body = Some(HttpRequestBody::FormURLEncoded(form));
+
+        request
+    }
+
+    /// Create a `DELETE` request with a JSON body serialized from `value`.
+    #[cfg(feature = "json")]
+    pub fn delete_json<T: Serialize + ?Sized>(
+        url: Url,
+        value: &T,
+    ) -> Result<HttpRequest<QK, QV, BK, BV, HK, HV>, HttpRequestError> {
+        let mut request = Self::delete(url);
+
+        request.body = Some(HttpRequestBody::json(value)?);
+
+        Ok(request)
+    }
+
     pub fn head(url: Url) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
         Self::new(HttpRequestMethod::HEAD, url)
     }
@@ -168,6 +433,129 @@ impl<
         Ok(Self::head(url))
     }
 
+    pub fn options(url: Url) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
+        Self::new(HttpRequestMethod::OPTIONS, url)
+    }
+
+    pub fn options_from_url_str<S: AsRef<str>>(
+        url: S,
+    ) -> Result<HttpRequest<QK, QV, BK, BV, HK, HV>, HttpRequestError> {
+        let url = Url::parse(url.as_ref())?;
+
+        Ok(Self::options(url))
+    }
+
+    /// Create a CONNECT request whose target is the host and port of the URL.
+    ///
+    /// Only the status and the headers of the response are returned. The tunnel itself is closed afterwards, and the connection is made to the host of the URL itself (or to the `resolve` or `unix_socket` override).
+    pub fn connect(url: Url) -> HttpRequest<QK, QV, BK, BV, HK, HV> {
+        Self::new(HttpRequestMethod::CONNECT, url)
+    }
+
+    pub fn connect_from_url_str<S: AsRef<str>>(
+        url: S,
+    ) -> Result<HttpRequest<QK, QV, BK, BV, HK, HV>, HttpRequestError> {
+        let url = Url::parse(url.as_ref())?;
+
+        Ok(Self::connect(url))
+    }
+
+    /// Send a CORS preflight request (`OPTIONS`) as a browser would do from `origin` before requesting `url` with `requested_method` and `requested_headers`.
+    pub fn preflight<O: AsRef<str>, S: AsRef<str>>(
+        url: Url,
+        origin: O,
+        requested_method: HttpRequestMethod,
+        requested_headers: &[S],
+    ) -> Result<CorsPreflight, HttpRequestError> {
+        let mut request = DefaultHttpRequest::options(url);
+
+        let mut headers = HashMap::new();
+
+        headers.insert("Origin".to_string(), origin.as_ref().to_string());
+        headers.insert(
+            "Access-Control-Request-Method".to_string(),
+            requested_method.get_str().to_string(),
+        );
+
+        if !requested_headers.is_empty() {
+            let requested_headers: Vec<&str> =
+                requested_headers.iter().map(|h| h.as_ref()).collect();
+
+            headers
+                .insert("Access-Control-Request-Headers".to_string(), requested_headers.join(", "));
+        }
+
+        request.headers = Some(headers);
+
+        let response = request.send()?;
+
+        Ok(CorsPreflight::from_response(&response))
+    }
+
+    /// Register a closure which is invoked on a 401 response to produce a fresh `Authorization` header value, after which the request is retried once.
+    #[inline]
+    pub fn on_unauthorized<F: FnMut() -> String + Send + 'static>(mut self, f: F) -> Self {
+        self.options.on_unauthorized = Some(Arc::new(Mutex::new(f)));
+
+        self
+    }
+
+    /// Append a path segment to the URL. The segment is percent-encoded, so slashes in it do not start new segments. A trailing slash of the URL is replaced rather than doubled.
+    pub fn push_path_segment<S: AsRef<str>>(&mut self, segment: S) -> Result<(), HttpRequestError> {
+        let mut segments = self
+            .url
+            .path_segments_mut()
+            .map_err(|_| HttpRequestError::Other("The URL cannot have path segments."))?;
+
+        segments.pop_if_empty().push(segment.as_ref());
+
+        Ok(())
+    }
+
+    /// Insert a query parameter, creating the `query` map if there is none.
+    #[inline]
+    pub fn add_query(&mut self, name: QK, value: QV) {
+        self.query.get_or_insert_with(HashMap::new).insert(name, value);
+    }
+
+    /// Set a header, replacing the one which has the same name (case-insensitively).
+    pub fn set_header(&mut self, name: HK, value: HV) {
+        let headers = self.headers.get_or_insert_with(HashMap::new);
+
+        headers.retain(|k, _| !k.as_ref().eq_ignore_ascii_case(name.as_ref()));
+
+        if let Some(extra_headers) = self.extra_headers.as_mut() {
+            extra_headers.retain(|(k, _)| !k.as_ref().eq_ignore_ascii_case(name.as_ref()));
+        }
+
+        headers.insert(name, value);
+    }
+
+    /// Append a header to `extra_headers`, keeping the existing values of the same name.
+    #[inline]
+    pub fn append_header(&mut self, name: HK, value: HV) {
+        self.extra_headers.get_or_insert_with(Vec::new).push((name, value));
+    }
+
+    /// Remove the header which has this name (case-insensitively).
+    #[inline]
+    pub fn remove_header<S: AsRef<str>>(&mut self, name: S) {
+        if let Some(headers) = self.headers.as_mut() {
+            headers.retain(|k, _| !k.as_ref().eq_ignore_ascii_case(name.as_ref()));
+        }
+
+        if let Some(extra_headers) = self.extra_headers.as_mut() {
+            extra_headers.retain(|(k, _)| !k.as_ref().eq_ignore_ascii_case(name.as_ref()));
+        }
+    }
+
+    /// Remove all headers, including `extra_headers`.
+    #[inline]
+    pub fn clear_headers(&mut self) {
+        self.headers = None;
+        self.extra_headers = None;
+    }
+
     /// Send a request and drop this sender.
     pub fn send(self) -> Result<HttpResponse, HttpRequestError> {
         Self::send_request_inner(
@@ -176,11 +564,18 @@ impl<
             &self.query,
             &self.body,
             &self.headers,
+            &self.extra_headers,
             &self.options,
-            self.options.max_redirect_count,
+            0,
         )
     }
 
+    /// Send a request and drop this sender. Unlike `send`, a 4xx or 5xx response is a `StatusError`.
+    #[inline]
+    pub fn send_checked(self) -> Result<HttpResponse, HttpRequestError> {
+        self.send()?.error_for_status()
+    }
+
     /// Send a request and preserve this sender so that it can be used again.
     #[inline]
     pub fn send_preserved(&self) -> Result<HttpResponse, HttpRequestError> {
@@ -190,36 +585,374 @@ impl<
             &self.query,
             &self.body,
             &self.headers,
+            &self.extra_headers,
             &self.options,
-            self.options.max_redirect_count,
+            0,
         )
     }
 
-    fn send_request_inner(
+    /// Send a request and give this sender back if the request succeeds, so that it can be used again.
+    #[inline]
+    pub fn send_reusable(self) -> Result<(HttpResponse, Self), HttpRequestError> {
+        let response = self.send_preserved()?;
+
+        Ok((response, self))
+    }
+
+    /// Send a request and read the body of the response into `buf`, which is cleared first, so that a buffer can be reused across requests. The size and time limits still apply. On an error, `buf` keeps the part of the body which has been received.
+    pub fn send_into(self, buf: &mut Vec<u8>) -> Result<HttpResponseHead, HttpRequestError> {
+        let received = Self::send_request_head_inner(
+            self.method,
+            self.url,
+            &self.query,
+            &self.body,
+            &self.headers,
+            &self.extra_headers,
+            &self.options,
+            0,
+            None,
+        )?;
+
+        let mut reader = received.reader;
+
+        buf.clear();
+
+        let mut buffer = [0u8; BUFFER_SIZE];
+
+        loop {
+            match reader.read_inner(&mut buffer)? {
+                0 => break,
+                c => buf.extend_from_slice(&buffer[0..c]),
+            }
+        }
+
+        Ok(received.head)
+    }
+
+    /// Send a request and write the body of the response, decompressed according to its `Content-Encoding`, to `writer` while it is being received, so that the whole body is never held in memory. The size and time limits still apply. In particular, `max_response_body_size` (1 MiB by default) limits the decompressed size, and exceeding it is a `TooLarge` error after the data before the limit has already been written, so raise it or set it to `None` for a large download.
+    pub fn send_decompressed_to_writer<W: Write>(
+        self,
+        writer: &mut W,
+    ) -> Result<HttpResponseHead, HttpRequestError> {
+        let received = Self::send_request_head_inner(
+            self.method,
+            self.url,
+            &self.query,
+            &self.body,
+            &self.headers,
+            &self.extra_headers,
+            &self.options,
+            0,
+            None,
+        )?;
+
+        let mut reader = received.reader;
+
+        let mut buffer = [0u8; BUFFER_SIZE];
+
+        loop {
+            match reader.read_inner(&mut buffer)? {
+                0 => break,
+                c => writer.write_all(&buffer[0..c])?,
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(received.head)
+    }
+
+    /// Send a request and return the status and the headers of the response, along with a reader of its body which is still being received.
+    pub fn send_streaming(
+        self,
+    ) -> Result<(HttpResponseHead, HttpResponseReader), HttpRequestError> {
+        let received = Self::send_request_head_inner(
+            self.method,
+            self.url,
+            &self.query,
+            &self.body,
+            &self.headers,
+            &self.extra_headers,
+            &self.options,
+            0,
+            None,
+        )?;
+
+        Ok((received.head, received.reader))
+    }
+
+    /// Send a request and parse the body of the response as a `text/event-stream` (server-sent events) while it is being received. Check `SseEventStream::head` for the status of the response, and consider disabling `max_connection_time` and `max_response_body_size` for a long-lived stream. The size of a single line or event is still limited by `SseEventStream::max_event_size`.
+    pub fn send_event_stream(self) -> Result<SseEventStream, HttpRequestError> {
+        let (head, reader) = self.send_streaming()?;
+
+        Ok(SseEventStream::new(head, reader))
+    }
+
+    /// Send a request which has an `Upgrade` header (e.g. for WebSocket). If the server responds with `101 Switching Protocols`, the connection is also returned for the caller to drive the new protocol.
+    pub fn send_upgrade(
+        self,
+    ) -> Result<(HttpResponseHead, Option<HttpUpgradedStream>), HttpRequestError> {
+        let received = Self::send_request_head_inner(
+            self.method,
+            self.url,
+            &self.query,
+            &self.body,
+            &self.headers,
+            &self.extra_headers,
+            &self.options,
+            0,
+            None,
+        )?;
+
+        let reader = received.reader;
+        let upgraded_buffer = received.upgraded_buffer;
+
+        let upgraded_stream = received
+            .upgraded_stream
+            .map(|stream| HttpUpgradedStream::new(stream, upgraded_buffer, reader));
+
+        Ok((received.head, upgraded_stream))
+    }
+
+    /// Build the method, the URL with the query, the headers and the body which would be sent, without doing any network I/O.
+    #[inline]
+    pub fn build_request_parts(&self) -> Result<HttpRequestParts<'_>, HttpRequestError> {
+        Self::build_request_parts_inner(
+            self.method,
+            self.url.clone(),
+            &self.query,
+            &self.body,
+            &self.headers,
+            &self.extra_headers,
+            self.options.send_default_user_agent,
+        )
+    }
+
+    /// Render the request which would be sent as a `curl` command line. See `HttpRequestParts::to_curl`.
+    #[inline]
+    pub fn to_curl(&self) -> Result<String, HttpRequestError> {
+        Ok(self.build_request_parts()?.to_curl())
+    }
+
+    fn build_request_parts_inner<'a>(
         method: HttpRequestMethod,
         mut url: Url,
         query: &Option<HashMap<QK, QV>>,
+        body: &'a Option<HttpRequestBody<BK, BV>>,
+        headers: &Option<HashMap<HK, HV>>,
+        extra_headers: &Option<Vec<(HK, HV)>>,
+        send_default_user_agent: bool,
+    ) -> Result<HttpRequestParts<'a>, HttpRequestError> {
+        append_query(&mut url, query);
+
+        let mut request_headers = Headers::new();
+
+        {
+            let mut has_user_agent = false;
+
+            let map = headers.iter().flat_map(|map| map.iter());
+            let extra = extra_headers.iter().flat_map(|extra| extra.iter().map(|(k, v)| (k, v)));
+
+            for (k, v) in map.chain(extra) {
+                let name = k.as_ref();
+                let value = v.as_ref().as_bytes();
+
+                if name.eq_ignore_ascii_case("User-Agent") {
+                    has_user_agent = true;
+                }
+
+                request_headers.append_raw(name.to_string(), value.to_vec());
+            }
+
+            if !has_user_agent && send_default_user_agent {
+                request_headers.append_raw("User-Agent", DEFAULT_USER_AGENT.as_bytes().to_vec());
+            }
+        }
+
+        let request_body = match body {
+            Some(body) => {
+                let (content_type, body) = match body {
+                    HttpRequestBody::Binary {
+                        content_type,
+                        body,
+                    } => (Some(content_type.to_string()), Cow::Borrowed(body.as_slice())),
+                    HttpRequestBody::Text {
+                        content_type,
+                        body,
+                    } => (Some(content_type.to_string()), Cow::Borrowed(body.as_bytes())),
+                    HttpRequestBody::FormURLEncoded(map) => {
+                        (
+                            Some(mime::APPLICATION_WWW_FORM_URLENCODED.to_string()),
+                            Cow::Owned(encode_form(map)),
+                        )
+                    }
+                    HttpRequestBody::FormURLEncodedOrdered(pairs) => {
+                        (
+                            Some(mime::APPLICATION_WWW_FORM_URLENCODED.to_string()),
+                            Cow::Owned(encode_form(pairs.iter().map(|(k, v)| (k, v)))),
+                        )
+                    }
+                    HttpRequestBody::Raw(body) => (None, Cow::Borrowed(body.as_slice())),
+                    HttpRequestBody::Multipart {
+                        boundary,
+                        parts,
+                    } => {
+                        (
+                            Some(format!("multipart/form-data; boundary={}", boundary)),
+                            Cow::Owned(multipart_part::encode_multipart(boundary, parts)),
+                        )
+                    }
+                };
+
+                // the Content-Type of the body is just a default value, like the User-Agent
+                if let Some(content_type) = content_type {
+                    if request_headers.get_raw("Content-Type").is_none() {
+                        request_headers.set_raw("Content-Type", vec![content_type.into_bytes()]);
+                    }
+                }
+
+                Some(body)
+            }
+            None => None,
+        };
+
+        if let Some(body) = request_body.as_ref() {
+            request_headers.set_raw("Content-Length", vec![body.len().to_string().into_bytes()]);
+        }
+
+        Ok(HttpRequestParts {
+            method,
+            url,
+            headers: request_headers,
+            body: request_body,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn send_request_inner(
+        method: HttpRequestMethod,
+        url: Url,
+        query: &Option<HashMap<QK, QV>>,
         body: &Option<HttpRequestBody<BK, BV>>,
         headers: &Option<HashMap<HK, HV>>,
+        extra_headers: &Option<Vec<(HK, HV)>>,
         options: &HttpRequestOptions,
-        redirection_counter: usize,
+        redirection_count: usize,
     ) -> Result<HttpResponse, HttpRequestError> {
+        let mut request_url = url.clone();
+
+        append_query(&mut request_url, query);
+
+        let ReceivedResponse {
+            head,
+            mut reader,
+            request_body_size,
+            start_time,
+            time_to_first_byte,
+            connection_timings,
+            ..
+        } = Self::send_request_head_inner(
+            method,
+            url,
+            query,
+            body,
+            headers,
+            extra_headers,
+            options,
+            redirection_count,
+            None,
+        )?;
+
+        let mut body = Vec::new();
+        let mut buffer = [0u8; BUFFER_SIZE];
+        let mut truncated = false;
+
+        #[cfg(feature = "body-hash")]
+        let mut hasher = options.body_hash_algorithm.map(BodyHasher::new);
+
+        loop {
+            match reader.read_inner(&mut buffer) {
+                Ok(0) => break,
+                Ok(c) => {
+                    #[cfg(feature = "body-hash")]
+                    if let Some(hasher) = hasher.as_mut() {
+                        hasher.update(&buffer[0..c]);
+                    }
+
+                    body.extend_from_slice(&buffer[0..c]);
+                }
+                Err(
+                    err @ HttpRequestError::TooLarge {
+                        ..
+                    },
+                ) => return Err(err),
+                Err(_) if options.return_partial_on_error => {
+                    truncated = true;
+
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(HttpResponse {
+            status_code: head.status_code,
+            headers: head.headers,
+            raw_headers: head.raw_headers,
+            body,
+            was_redirected: head.was_redirected,
+            url: head.url,
+            request_method: method,
+            request_url,
+            remote_addr: head.remote_addr,
+            truncated,
+            request_body_size,
+            response_body_size: reader.size(),
+            #[cfg(feature = "body-hash")]
+            body_hash: hasher.map(BodyHasher::finish),
+            timings: build_timings(connection_timings, time_to_first_byte, start_time.elapsed()),
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn send_request_head_inner(
+        method: HttpRequestMethod,
+        url: Url,
+        query: &Option<HashMap<QK, QV>>,
+        body: &Option<HttpRequestBody<BK, BV>>,
+        headers: &Option<HashMap<HK, HV>>,
+        extra_headers: &Option<Vec<(HK, HV)>>,
+        options: &HttpRequestOptions,
+        redirection_count: usize,
+        original_url: Option<&Url>,
+    ) -> Result<ReceivedResponse, HttpRequestError> {
+        check_cancelled(&options.cancel_token)?;
+
+        // e.g. `file://` and `ftp://`, also as redirection targets
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(HttpRequestError::Other("Only http and https schemes are supported."));
+        }
+
+        if options.https_only && url.scheme() != "https" {
+            return Err(HttpRequestError::Other("Only HTTPS URLs are allowed."));
+        }
+
         match url.host() {
             Some(host) => {
                 if !options.allow_local {
-                    match host {
-                        Host::Ipv4(ipv4) => {
-                            if is_local_ipv4(ipv4) {
-                                return Err(HttpRequestError::LocalNotAllow);
-                            }
-                        }
-                        Host::Ipv6(ipv6) => {
-                            if is_local_ipv6(&ipv6) {
-                                return Err(HttpRequestError::LocalNotAllow);
-                            }
-                        }
-                        Host::Domain(domain) => {
-                            if domain == "localhost" {
+                    if is_local_host(&host) {
+                        return Err(HttpRequestError::LocalNotAllow);
+                    }
+
+                    if let Host::Domain(domain) = host {
+                        // the overridden address is the one which will be connected to
+                        let resolved = options
+                            .resolve
+                            .iter()
+                            .find(|(host, _)| host.eq_ignore_ascii_case(domain));
+
+                        if let Some((_, ip)) = resolved {
+                            if is_local_ip(ip) {
                                 return Err(HttpRequestError::LocalNotAllow);
                             }
                         }
@@ -229,286 +962,941 @@ impl<
             None => return Err(HttpRequestError::Other("A valid HTTP URL needs contains a host.")),
         }
 
-        if let Some(map) = query {
-            let mut query = url.query_pairs_mut();
-
-            for (k, v) in map {
-                query.append_pair(k.as_ref(), v.as_ref());
+        let HttpRequestParts {
+            url,
+            headers: mut request_headers,
+            body: request_body,
+            ..
+        } = Self::build_request_parts_inner(
+            method,
+            url,
+            query,
+            body,
+            headers,
+            extra_headers,
+            options.send_default_user_agent,
+        )?;
+
+        // a pinned `Host` header must not be sent to another host which a redirection leads to
+        let is_original_host =
+            original_url.is_none_or(|original_url| original_url.host_str() == url.host_str());
+
+        if is_original_host {
+            if let Some(host) = options.host_override.as_ref() {
+                request_headers.set_raw("Host", vec![host.clone().into_bytes()]);
             }
+        } else {
+            request_headers.remove_raw("Host");
+        }
+
+        if options.disable_keep_alive && request_headers.get_raw("Connection").is_none() {
+            request_headers.set_raw("Connection", vec![b"close".to_vec()]);
+        }
+
+        let proxy = proxy_url(&url, options)?;
+
+        let has_request_body = request_body.as_ref().is_some_and(|body| !body.is_empty());
+        let request_body_size = request_body.as_ref().map_or(0, |body| body.len());
+
+        // hyper cannot wait between the headers and the body, drops the body of a GET request, and keeps the bytes which it has buffered after a 101 response, so such requests are written by hand, which is not done through a proxy
+        let expect_continue = options.use_expect_continue
+            && method != HttpRequestMethod::CONNECT
+            && proxy.is_none()
+            && has_request_body;
+
+        let write_by_hand = expect_continue
+            || (proxy.is_none()
+                && ((method == HttpRequestMethod::GET && has_request_body)
+                    || request_headers.get_raw("Upgrade").is_some()));
+
+        if expect_continue {
+            request_headers.set_raw("Expect", vec![b"100-continue".to_vec()]);
         }
 
         let ssl = NativeTlsClient::new().unwrap();
-        let connector = HttpsConnector::new(ssl);
+        let mut connector = Connector::new(ssl, options);
+
+        // the connection of a request which asks for an upgrade may be handed over to the caller
+        let captured_stream = if request_headers.get_raw("Upgrade").is_some() {
+            Some(connector.capture_stream())
+        } else {
+            None
+        };
+
+        let mut timeout = if options.max_connection_time > 0 {
+            Some(Duration::from_millis(options.max_connection_time))
+        } else {
+            None
+        };
+
+        if let Some(deadline) = options.deadline {
+            let now = Instant::now();
+
+            if now >= deadline {
+                return Err(HttpRequestError::TimeOut(Duration::from_millis(0)));
+            }
 
-        let mut client = Client::with_connector(connector);
+            let remaining = deadline - now;
 
-        if options.max_connection_time > 0 {
-            let timeout = Duration::from_millis(options.max_connection_time);
+            timeout = Some(timeout.map_or(remaining, |timeout| timeout.min(remaining)));
+        }
 
-            client.set_read_timeout(Some(timeout));
-            client.set_write_timeout(Some(timeout));
+        // the read timeout of the socket bounds every single read, so it also works as the idle timeout
+        let read_timeout = match options.idle_read_timeout {
+            Some(idle_read_timeout) => {
+                Some(timeout.map_or(idle_read_timeout, |timeout| timeout.min(idle_read_timeout)))
+            }
+            None => timeout,
+        };
+
+        if let Some(trace_writer) = options.trace_writer.as_ref() {
+            // a failure of tracing does not fail the request
+            let _ = write_trace(
+                &mut *trace_writer.lock().unwrap(),
+                method,
+                &url,
+                &request_headers,
+                request_body.as_deref(),
+            );
         }
 
-        client.set_redirect_policy(RedirectPolicy::FollowNone);
+        if method == HttpRequestMethod::CONNECT {
+            return send_connect(&url, request_headers, &connector, timeout, read_timeout, options);
+        }
 
-        let mut request: RequestBuilder =
-            client.request(Method::from_str(method.get_str()).unwrap(), url.clone());
+        let connection_timings = connector.timings();
+        let peer_addr = connector.peer_addr();
 
-        let mut request_headers = Headers::new();
+        let start_time = Instant::now();
 
-        {
-            let has_user_agent = match headers {
-                Some(map) => {
-                    let mut has_user_agent = false;
+        let (status_code, response_headers, response, upgraded_buffer) = if write_by_hand {
+            send_by_hand(
+                method,
+                &url,
+                request_headers,
+                request_body.as_deref().unwrap_or_default(),
+                expect_continue,
+                &connector,
+                timeout,
+                read_timeout,
+                start_time,
+                options,
+            )?
+        } else {
+            let mut client = match proxy {
+                Some(proxy) => {
+                    let mut proxy_config = ProxyConfig::new(
+                        "http",
+                        proxy.host_str().unwrap().to_string(),
+                        proxy.port_or_known_default().unwrap(),
+                        connector,
+                        NativeTlsClient::new().unwrap(),
+                    );
 
-                    for (k, v) in map {
-                        let name = k.as_ref();
-                        let value = v.as_ref().as_bytes();
+                    proxy_config.set_pool_config(None);
 
-                        if name.eq_ignore_ascii_case("User-Agent") {
-                            has_user_agent = true;
+                    Client::with_proxy_config(proxy_config)
+                }
+                // a connection which may be upgraded is not put back into the pool
+                None => {
+                    match options.connection_pool.as_ref() {
+                        Some(pool) if captured_stream.is_none() => {
+                            Client::with_connector(InterimResponseFilter {
+                                connector: PooledConnector {
+                                    connector,
+                                    pool: pool.clone(),
+                                },
+                                enabled: options.skip_interim_responses,
+                                limits: InterimResponseLimits::new(options, start_time),
+                            })
+                        }
+                        _ => {
+                            Client::with_connector(InterimResponseFilter {
+                                connector,
+                                enabled: options.skip_interim_responses,
+                                limits: InterimResponseLimits::new(options, start_time),
+                            })
                         }
-
-                        request_headers.append_raw(name.to_string(), value.to_vec());
                     }
-
-                    has_user_agent
                 }
-                None => false,
             };
 
-            if !has_user_agent {
-                request_headers.append_raw("User-Agent", DEFAULT_USER_AGENT.as_bytes().to_vec());
+            if read_timeout.is_some() {
+                client.set_read_timeout(read_timeout);
+            }
+
+            if timeout.is_some() {
+                client.set_write_timeout(timeout);
             }
-        }
 
-        let mut body_owner = None;
+            client.set_redirect_policy(RedirectPolicy::FollowNone);
 
-        if let Some(body) = body {
-            match body {
-                HttpRequestBody::Binary {
-                    content_type,
-                    body,
-                } => {
-                    request_headers
-                        .set_raw("Content-Type", vec![content_type.to_string().into_bytes()]);
+            let mut request: RequestBuilder =
+                client.request(Method::from_str(method.get_str()).unwrap(), url.clone());
 
-                    let body_size = body.len();
+            if let Some(body) = request_body.as_ref() {
+                request = request.body(Body::BufBody(body, body.len()));
+            }
 
-                    request_headers
-                        .set_raw("Content-Length", vec![body_size.to_string().into_bytes()]);
+            request = request.headers(request_headers);
 
-                    request = request.body(Body::BufBody(body, body_size));
+            let response = request.send().map_err(|err| {
+                match err {
+                    HyperError::Io(ref io_err) if is_timeout_error(io_err) => {
+                        HttpRequestError::TimeOut(start_time.elapsed())
+                    }
+                    // e.g. a limit of skipping interim responses
+                    HyperError::Io(io_err)
+                        if io_err.get_ref().is_some_and(|err| err.is::<HttpRequestError>()) =>
+                    {
+                        *io_err.into_inner().unwrap().downcast::<HttpRequestError>().unwrap()
+                    }
+                    _ => HttpRequestError::from(err),
                 }
-                HttpRequestBody::Text {
-                    content_type,
-                    body,
-                } => {
-                    request_headers
-                        .set_raw("Content-Type", vec![content_type.to_string().into_bytes()]);
+            })?;
 
-                    let body_size = body.len();
+            (
+                response.status.to_u16(),
+                response.headers.clone(),
+                Box::new(response) as Box<dyn Read + Send>,
+                Vec::new(),
+            )
+        };
 
-                    request_headers
-                        .set_raw("Content-Length", vec![body_size.to_string().into_bytes()]);
+        let time_to_first_byte = start_time.elapsed();
 
-                    request = request.body(Body::BufBody(body.as_ref(), body_size));
-                }
-                HttpRequestBody::FormURLEncoded(map) => {
-                    let query = {
-                        let mut url = Url::parse("q:")?;
-                        {
-                            let mut query = url.query_pairs_mut();
-                            for (k, v) in map {
-                                query.append_pair(k.as_ref(), v.as_ref());
-                            }
-                        }
-                        match url.query() {
-                            Some(q) => q.as_bytes().to_vec(),
-                            None => Vec::new(),
+        check_timeout(start_time, options.max_connection_time, options.deadline)?;
+
+        let (headers_raw_map, raw_headers) = collect_headers(&response_headers, options)?;
+
+        let remote_addr = *peer_addr.lock().unwrap();
+
+        if status_code == 401 {
+            if let Some(on_unauthorized) = options.on_unauthorized.as_ref() {
+                let authorization = (on_unauthorized.lock().unwrap())();
+
+                let mut headers = to_string_headers(headers);
+                let mut extra_headers = to_string_extra_headers(extra_headers);
+
+                headers.retain(|k, _| !k.eq_ignore_ascii_case("Authorization"));
+                extra_headers.retain(|(k, _)| !k.eq_ignore_ascii_case("Authorization"));
+                headers.insert("Authorization".to_string(), authorization);
+
+                // retry only once
+                let mut options = options.clone();
+                options.on_unauthorized = None;
+
+                drop(response);
+
+                return HttpRequest::<QK, QV, BK, BV, String, String>::send_request_head_inner(
+                    method,
+                    url,
+                    query,
+                    body,
+                    &Some(headers),
+                    &Some(extra_headers),
+                    &options,
+                    redirection_count,
+                    original_url,
+                )
+                .map(|received| received.add_request_body_size(request_body_size));
+            }
+        }
+
+        let redirect_method_policy = if redirection_count < options.max_redirect_count {
+            options.redirect_behavior.get(&status_code).copied()
+        } else {
+            None
+        };
+
+        if let Some(redirect_method_policy) = redirect_method_policy {
+            let location_url = match find_header(&headers_raw_map, "location") {
+                // relative references (including `//host/path`, `?query` and `..` segments) are resolved against the current URL
+                Some(location) => {
+                    match url.join(location.trim()) {
+                        Ok(location_url) => location_url,
+                        Err(_) => {
+                            return Err(HttpRequestError::RedirectError {
+                                message: "Cannot parse the `location` field in headers.",
+                                url: Some(location.to_string()),
+                                status: Some(status_code),
+                            });
                         }
+                    }
+                }
+                None => {
+                    return Err(HttpRequestError::RedirectError {
+                        message: "Cannot get the `location` field in headers.",
+                        url: None,
+                        status: Some(status_code),
+                    });
+                }
+            };
+
+            let location_url = match options.redirect_handler.as_ref() {
+                // the handler takes the place of the checks below
+                Some(redirect_handler) => {
+                    let head = HttpResponseHead {
+                        status_code,
+                        headers: headers_raw_map.clone(),
+                        raw_headers: raw_headers.clone(),
+                        was_redirected: redirection_count > 0,
+                        url: url.clone(),
+                        remote_addr,
                     };
 
-                    request_headers
-                        .set_raw("Content-Type", vec![b"x-www-form-urlencoded".to_vec()]);
+                    match (redirect_handler.lock().unwrap())(&location_url, status_code, &head) {
+                        RedirectAction::Follow(location_url) => Some(location_url),
+                        RedirectAction::Stop => None,
+                        RedirectAction::Error => {
+                            return Err(HttpRequestError::RedirectError {
+                                message: "The redirection has been rejected.",
+                                url: Some(location_url.to_string()),
+                                status: Some(status_code),
+                            });
+                        }
+                    }
+                }
+                // with the `https_only` option, a redirection to a non-HTTPS URL is not followed and the 3xx response is returned
+                None if options.https_only && location_url.scheme() != "https" => None,
+                None => {
+                    check_redirect(&url, &location_url, status_code, options)?;
 
-                    let body_size = query.len();
+                    Some(location_url)
+                }
+            };
 
-                    request_headers
-                        .set_raw("Content-Length", vec![body_size.to_string().into_bytes()]);
+            if let Some(location_url) = location_url {
+                let (method, body) = match redirect_method_policy {
+                    RedirectMethodPolicy::Keep => (method, body),
+                    RedirectMethodPolicy::Get => (HttpRequestMethod::GET, &None),
+                };
 
-                    body_owner = Some(query);
+                drop(headers_raw_map);
+                drop(request_body);
+                drop(response);
 
-                    if let Some(body) = body_owner.as_ref() {
-                        request = request.body(Body::BufBody(body.as_ref(), body_size));
-                    }
+                return Self::send_request_head_inner(
+                    method,
+                    location_url,
+                    query,
+                    body,
+                    headers,
+                    extra_headers,
+                    options,
+                    redirection_count + 1,
+                    Some(original_url.unwrap_or(&url)),
+                )
+                .map(|received| received.add_request_body_size(request_body_size));
+            }
+        }
+
+        // responses to HEAD requests, 1xx, 204 and 304 responses have no body whatever their Content-Length is
+        let has_body = method != HttpRequestMethod::HEAD
+            && status_code / 100 != 1
+            && status_code != 204
+            && status_code != 304;
+
+        let reader = HttpResponseReader::new(
+            response,
+            status_code,
+            &headers_raw_map,
+            has_body,
+            options,
+            start_time,
+        );
+
+        Ok(ReceivedResponse {
+            head: HttpResponseHead {
+                status_code,
+                headers: headers_raw_map,
+                raw_headers,
+                was_redirected: redirection_count > 0,
+                url,
+                remote_addr,
+            },
+            reader,
+            request_body_size,
+            start_time,
+            time_to_first_byte,
+            connection_timings,
+            upgraded_buffer,
+            upgraded_stream: if status_code == 101 {
+                captured_stream.and_then(|captured_stream| captured_stream.lock().unwrap().take())
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// A response whose body has not been read yet.
+struct ReceivedResponse {
+    head: HttpResponseHead,
+    reader: HttpResponseReader,
+    request_body_size: usize,
+    start_time: Instant,
+    time_to_first_byte: Duration,
+    connection_timings: Option<Arc<Mutex<ConnectionTimings>>>,
+    /// The bytes after the headers of a 101 response which have already been read from the connection.
+    upgraded_buffer: Vec<u8>,
+    upgraded_stream: Option<ConnectionStream>,
+}
+
+impl ReceivedResponse {
+    /// Count the body of an earlier request (of a redirection or a 401 retry) as well.
+    #[inline]
+    fn add_request_body_size(mut self, size: usize) -> ReceivedResponse {
+        self.request_body_size += size;
+
+        self
+    }
+}
+
+/// Collect the response headers into a map with lowercased names, except the `case_preserved_headers` ones (and the raw list if the `preserve_raw_headers` option is enabled).
+#[allow(clippy::type_complexity)]
+fn collect_headers(
+    headers: &Headers,
+    options: &HttpRequestOptions,
+) -> Result<(HashMap<String, String>, Option<Vec<(String, String)>>), HttpRequestError> {
+    let mut headers_raw_map = HashMap::new();
+    let mut raw_headers = if options.preserve_raw_headers {
+        Some(Vec::new())
+    } else {
+        None
+    };
+    let mut headers_size = 0;
+    let mut headers_count = 0;
+
+    for header in headers.iter() {
+        // repeated headers are merged into one item
+        headers_count += headers.get_raw(header.name()).map_or(1, |values| values.len());
+
+        if headers_count > options.max_header_count {
+            return Err(HttpRequestError::TooManyHeaders {
+                limit: options.max_header_count,
+            });
+        }
+
+        let name = if options
+            .case_preserved_headers
+            .iter()
+            .any(|preserved| preserved.eq_ignore_ascii_case(header.name()))
+        {
+            header.name().to_string()
+        } else {
+            header.name().to_lowercase()
+        };
+        let value = header.value_string();
+
+        headers_size += name.len() + value.len();
+
+        if headers_size > options.max_response_headers_size {
+            return Err(HttpRequestError::HeadersTooLarge);
+        }
+
+        if let Some(raw_headers) = raw_headers.as_mut() {
+            if let Some(values) = headers.get_raw(header.name()) {
+                for value in values {
+                    raw_headers.push((
+                        header.name().to_string(),
+                        String::from_utf8_lossy(value).into_owned(),
+                    ));
                 }
             }
         }
 
-        request = request.headers(request_headers);
+        headers_raw_map.insert(name, value);
+    }
+
+    Ok((headers_raw_map, raw_headers))
+}
 
-        let start_time = Instant::now();
+/// Check a redirection against the `allow_https_downgrade` and `same_host_redirects_only` options.
+pub(crate) fn check_redirect(
+    url: &Url,
+    location_url: &Url,
+    status_code: u16,
+    options: &HttpRequestOptions,
+) -> Result<(), HttpRequestError> {
+    if !options.allow_https_downgrade && url.scheme() == "https" && location_url.scheme() == "http"
+    {
+        return Err(HttpRequestError::RedirectError {
+            message: "HTTPS to HTTP downgrade not allowed",
+            url: Some(location_url.to_string()),
+            status: Some(status_code),
+        });
+    }
+
+    if options.same_host_redirects_only && location_url.host_str() != url.host_str() {
+        return Err(HttpRequestError::RedirectError {
+            message: "Redirection to another host not allowed",
+            url: Some(location_url.to_string()),
+            status: Some(status_code),
+        });
+    }
+
+    Ok(())
+}
+
+/// Get the value of a header from a map collected by `collect_headers`. Names are lowercased except the `case_preserved_headers` ones, which need a case-insensitive scan.
+pub(crate) fn find_header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    let name = name.to_lowercase();
+
+    headers
+        .get(&name)
+        .or_else(|| headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(&name)).map(|(_, v)| v))
+        .map(|v| v.as_str())
+}
+
+/// hyper can only write the path of a URL as the request target, so a CONNECT request, which targets an authority, is written by hand.
+fn send_connect(
+    url: &Url,
+    mut request_headers: Headers,
+    connector: &Connector,
+    timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    options: &HttpRequestOptions,
+) -> Result<ReceivedResponse, HttpRequestError> {
+    let host = url.host_str().unwrap();
+    let port = match url.port_or_known_default() {
+        Some(port) => port,
+        None => return Err(HttpRequestError::Other("The port of the URL is unknown.")),
+    };
+
+    let authority = format!("{}:{}", host, port);
+
+    if request_headers.get_raw("Host").is_none() {
+        request_headers.set_raw("Host", vec![authority.clone().into_bytes()]);
+    }
+
+    let start_time = Instant::now();
+
+    let mut stream = connector.connect(host, port, url.scheme())?;
+
+    let connection_timings = connector.timings();
 
-        let mut response = request.send()?;
+    stream.set_read_timeout(read_timeout)?;
+    stream.set_write_timeout(timeout)?;
 
-        let u64_max = u128::from(u64::max_value());
+    let result = write!(stream, "CONNECT {} HTTP/1.1\r\n{}\r\n", authority, request_headers)
+        .and_then(|_| stream.flush());
 
-        if options.max_connection_time > 0 {
-            let elapsed = start_time.elapsed();
+    if let Err(err) = result {
+        return Err(if is_timeout_error(&err) {
+            HttpRequestError::TimeOut(start_time.elapsed())
+        } else {
+            err.into()
+        });
+    }
 
-            let millis = elapsed.as_millis();
-            if millis > u64_max || millis as u64 > options.max_connection_time {
-                return Err(HttpRequestError::TimeOut);
+    let incoming = parse_response(&mut BufReader::new(&mut stream)).map_err(|err| {
+        match err {
+            HyperError::Io(ref io_err) if is_timeout_error(io_err) => {
+                HttpRequestError::TimeOut(start_time.elapsed())
             }
+            _ => HttpRequestError::from(err),
         }
+    })?;
+
+    let time_to_first_byte = start_time.elapsed();
+
+    let (headers_raw_map, raw_headers) = collect_headers(&incoming.headers, options)?;
+
+    let _ = stream.close(Shutdown::Both);
 
-        let status_code = response.status.to_u16();
+    let reader = HttpResponseReader::new(
+        Box::new(io::empty()),
+        incoming.subject.0,
+        &headers_raw_map,
+        false,
+        options,
+        start_time,
+    );
 
-        let mut headers_raw_map = HashMap::new();
+    Ok(ReceivedResponse {
+        head: HttpResponseHead {
+            status_code: incoming.subject.0,
+            headers: headers_raw_map,
+            raw_headers,
+            was_redirected: false,
+            url: url.clone(),
+            remote_addr: *connector.peer_addr().lock().unwrap(),
+        },
+        reader,
+        request_body_size: 0,
+        start_time,
+        time_to_first_byte,
+        connection_timings,
+        upgraded_buffer: Vec::new(),
+        upgraded_stream: None,
+    })
+}
+
+/// Write a request by hand instead of through hyper. With `expect_continue`, the headers are written with `Expect: 100-continue`, and the body only after a `100 Continue` response (or if the server does not respond in time), so that a final response such as 401 or 413 avoids sending it. Otherwise the body is written right after the headers (hyper drops the body of a GET request).
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn send_by_hand(
+    method: HttpRequestMethod,
+    url: &Url,
+    mut request_headers: Headers,
+    body: &[u8],
+    expect_continue: bool,
+    connector: &Connector,
+    timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    start_time: Instant,
+    options: &HttpRequestOptions,
+) -> Result<(u16, Headers, Box<dyn Read + Send>, Vec<u8>), HttpRequestError> {
+    let host = url.host_str().unwrap();
+    let port = match url.port_or_known_default() {
+        Some(port) => port,
+        None => return Err(HttpRequestError::Other("The port of the URL is unknown.")),
+    };
+
+    if request_headers.get_raw("Host").is_none() {
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+
+        request_headers.set_raw("Host", vec![host.into_bytes()]);
+    }
 
-        for header in response.headers.iter() {
-            headers_raw_map.insert(header.name().to_lowercase(), header.value_string());
+    let map_io_error = |err: io::Error| {
+        if is_timeout_error(&err) {
+            HttpRequestError::TimeOut(start_time.elapsed())
+        } else {
+            HttpRequestError::IOError(err)
         }
+    };
 
-        if redirection_counter > 0 && status_code / 100 == 3 {
-            let location_url = match headers_raw_map.get("location") {
-                Some(location) => {
-                    match Url::parse(location) {
-                        Ok(mut location_url) => {
-                            if let Some(host) = url.host().as_ref() {
-                                if location_url.host().is_none() {
-                                    let username = url.username();
-                                    if !username.is_empty() {
-                                        location_url.set_username(username).unwrap();
-                                    }
-
-                                    location_url.set_host(Some(&host.to_string())).unwrap();
-
-                                    if let Some(port) = url.port() {
-                                        location_url.set_port(Some(port)).unwrap();
-                                    }
-                                }
-                            }
+    let map_hyper_error = |err: HyperError| {
+        match err {
+            HyperError::Io(ref io_err) if is_timeout_error(io_err) => {
+                HttpRequestError::TimeOut(start_time.elapsed())
+            }
+            _ => HttpRequestError::from(err),
+        }
+    };
+
+    let mut stream = connector.connect(host, port, url.scheme())?;
+
+    stream.set_write_timeout(timeout)?;
+
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\n{}\r\n",
+        method,
+        &url[Position::BeforePath..Position::AfterQuery],
+        request_headers
+    )
+    .and_then(|_| {
+        if expect_continue {
+            Ok(())
+        } else {
+            stream.write_all(body)
+        }
+    })
+    .and_then(|_| stream.flush())
+    .map_err(map_io_error)?;
 
-                            location_url
-                        }
-                        Err(_) => {
-                            let mut location_url = String::new();
+    // well below the read timeout, so that a server which ignores the expectation still gets the body in time
+    let continue_timeout = read_timeout.map_or(EXPECT_CONTINUE_TIMEOUT, |timeout| {
+        (timeout / 2).clamp(Duration::from_millis(1), EXPECT_CONTINUE_TIMEOUT)
+    });
+
+    // a clone shares the connection (and the TLS session), so the body can be written while the responses are read
+    let mut writer = stream.clone();
+    let mut reader = BufReader::new(stream);
 
-                            location_url.push_str(url.scheme());
-                            location_url.push_str("://");
+    let mut body_sent = !expect_continue;
 
-                            if let Some(host) = url.host().as_ref() {
-                                let username = url.username();
-                                if !username.is_empty() {
-                                    location_url.push_str(username);
-                                    location_url.push('@');
-                                }
+    if body_sent {
+        reader.get_ref().set_read_timeout(read_timeout)?;
+    } else {
+        reader.get_ref().set_read_timeout(Some(continue_timeout))?;
+    }
 
-                                location_url.push_str(&host.to_string());
+    let mut interim_count = 0;
 
-                                if let Some(port) = url.port() {
-                                    location_url.write_fmt(format_args!(":{}", port)).unwrap();
-                                }
-                            }
+    let incoming = loop {
+        check_timeout(start_time, options.max_connection_time, options.deadline)?;
 
-                            slash_formatter::concat_with_slash_in_place(
-                                &mut location_url,
-                                location,
-                            );
-
-                            match Url::parse(&location_url) {
-                                Ok(location_url) => location_url,
-                                Err(_) => {
-                                    return Err(HttpRequestError::RedirectError(
-                                        "Cannot parse the `location` field in headers.",
-                                    ));
-                                }
-                            }
+        match parse_response(&mut reader) {
+            Ok(incoming) => {
+                match incoming.subject.0 {
+                    100 if !body_sent => {
+                        writer
+                            .write_all(body)
+                            .and_then(|_| writer.flush())
+                            .map_err(map_io_error)?;
+
+                        body_sent = true;
+
+                        reader.get_ref().set_read_timeout(read_timeout)?;
+                    }
+                    // other interim responses are skipped
+                    100 | 102..=199 => {
+                        interim_count += 1;
+
+                        if interim_count > MAX_INTERIM_RESPONSES {
+                            return Err(HttpRequestError::Other(
+                                "The server has sent too many interim responses.",
+                            ));
                         }
                     }
+                    _ => break incoming,
                 }
-                None => {
-                    return Err(HttpRequestError::RedirectError(
-                        "Cannot get the `location` field in headers.",
-                    ));
-                }
-            };
+            }
+            // the server does not answer the expectation, so the body is sent anyway
+            Err(HyperError::Io(ref err)) if !body_sent && is_timeout_error(err) => {
+                writer.write_all(body).and_then(|_| writer.flush()).map_err(map_io_error)?;
 
-            match status_code {
-                303 => {
-                    drop(headers_raw_map);
-                    drop(body_owner);
-                    drop(response);
-                    drop(client);
-
-                    return Self::send_request_inner(
-                        HttpRequestMethod::GET,
-                        location_url,
-                        query,
-                        &None,
-                        headers,
-                        options,
-                        redirection_counter,
-                    );
-                }
-                301 | 302 | 307 | 308 => {
-                    drop(headers_raw_map);
-                    drop(body_owner);
-                    drop(response);
-                    drop(client);
-
-                    return Self::send_request_inner(
-                        method,
-                        location_url,
-                        query,
-                        body,
-                        headers,
-                        options,
-                        redirection_counter,
-                    );
-                }
-                _ => {
-                    return Err(HttpRequestError::RedirectError("Unsupported redirection status."));
-                }
+                body_sent = true;
+
+                reader.get_ref().set_read_timeout(read_timeout)?;
             }
+            Err(err) => return Err(map_hyper_error(err)),
         }
+    };
 
-        let mut sum_size = 0;
-        let mut body = Vec::new();
-        let mut buffer = [0u8; BUFFER_SIZE];
+    let status_code = incoming.subject.0;
 
-        loop {
-            let c = response.read(&mut buffer)?;
+    // the new protocol may have started right after the headers
+    let upgraded_buffer = if status_code == 101 {
+        let buffer = reader.get_buf().to_vec();
 
-            if c == 0 {
-                break;
-            }
+        reader.consume(buffer.len());
 
-            sum_size += c;
+        buffer
+    } else {
+        Vec::new()
+    };
 
-            if sum_size > options.max_response_body_size {
-                return Err(HttpRequestError::TooLarge);
+    // the connection cannot be reused if the body has not been sent
+    if !body_sent {
+        let _ = writer.close(Shutdown::Write);
+    }
+
+    // responses to HEAD requests, 1xx, 204 and 304 responses have no body whatever their Content-Length is
+    let has_body = method != HttpRequestMethod::HEAD
+        && status_code / 100 != 1
+        && status_code != 204
+        && status_code != 304;
+
+    let response: HttpReader<BufReader<ConnectionStream>> =
+        if !has_body {
+            HttpReader::EmptyReader(reader)
+        } else if incoming.headers.get_raw("Transfer-Encoding").is_some_and(|values| {
+            values.iter().any(|v| v.to_ascii_lowercase().ends_with(b"chunked"))
+        }) {
+            HttpReader::ChunkedReader(reader, None)
+        } else {
+            match incoming.headers.get::<ContentLength>() {
+                Some(&ContentLength(length)) => HttpReader::SizedReader(reader, length),
+                None => HttpReader::EofReader(reader),
             }
+        };
 
-            body.extend_from_slice(&buffer[0..c]);
+    Ok((status_code, incoming.headers, Box::new(response), upgraded_buffer))
+}
 
-            if options.max_connection_time > 0 {
-                let elapsed = start_time.elapsed();
+fn write_trace(
+    writer: &mut dyn Write,
+    method: HttpRequestMethod,
+    url: &Url,
+    headers: &Headers,
+    body: Option<&[u8]>,
+) -> io::Result<()> {
+    writeln!(writer, "> {} {}", method, url)?;
+
+    for header in headers.iter() {
+        writeln!(writer, "> {}: {}", header.name(), header.value_string())?;
+    }
 
-                let millis = elapsed.as_millis();
-                if millis > u64_max || millis as u64 > options.max_connection_time {
-                    return Err(HttpRequestError::TimeOut);
-                }
+    if let Some(body) = body {
+        match headers.get_raw("Content-Type") {
+            Some(content_type) => {
+                writeln!(
+                    writer,
+                    "> [{} bytes of {}]",
+                    body.len(),
+                    String::from_utf8_lossy(&content_type[0])
+                )?
             }
+            None => writeln!(writer, "> [{} bytes]", body.len())?,
         }
+    }
 
-        Ok(HttpResponse {
-            status_code,
-            headers: headers_raw_map,
-            body,
-        })
+    writer.flush()
+}
+
+#[inline]
+fn build_timings(
+    connection_timings: Option<Arc<Mutex<ConnectionTimings>>>,
+    time_to_first_byte: Duration,
+    total: Duration,
+) -> Option<Timings> {
+    connection_timings.map(|connection_timings| {
+        let connection_timings = connection_timings.lock().unwrap();
+
+        Timings {
+            dns: connection_timings.dns,
+            connect: connection_timings.connect,
+            tls: connection_timings.tls,
+            time_to_first_byte,
+            total,
+        }
+    })
+}
+
+#[inline]
+fn append_query<QK: AsRef<str>, QV: AsRef<str>>(url: &mut Url, query: &Option<HashMap<QK, QV>>) {
+    if let Some(map) = query {
+        let mut query = url.query_pairs_mut();
+
+        for (k, v) in map {
+            query.append_pair(k.as_ref(), v.as_ref());
+        }
+    }
+}
+
+/// Encode the pairs as `application/x-www-form-urlencoded`, in which spaces are encoded as `+` and `+` as `%2B`.
+fn encode_form<
+    'a,
+    K: AsRef<str> + 'a,
+    V: AsRef<str> + 'a,
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+>(
+    pairs: I,
+) -> Vec<u8> {
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+
+    for (k, v) in pairs {
+        serializer.append_pair(k.as_ref(), v.as_ref());
+    }
+
+    serializer.finish().into_bytes()
+}
+
+#[inline]
+fn to_string_extra_headers<HK: AsRef<str>, HV: AsRef<str>>(
+    extra_headers: &Option<Vec<(HK, HV)>>,
+) -> Vec<(String, String)> {
+    match extra_headers {
+        Some(extra_headers) => {
+            extra_headers
+                .iter()
+                .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
+                .collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+#[inline]
+fn to_string_headers<HK: AsRef<str>, HV: AsRef<str>>(
+    headers: &Option<HashMap<HK, HV>>,
+) -> HashMap<String, String> {
+    match headers {
+        Some(map) => {
+            map.iter().map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string())).collect()
+        }
+        None => HashMap::new(),
+    }
+}
+
+#[inline]
+fn check_cancelled(cancel_token: &Option<Arc<AtomicBool>>) -> Result<(), HttpRequestError> {
+    match cancel_token {
+        Some(cancel_token) if cancel_token.load(Ordering::Relaxed) => {
+            Err(HttpRequestError::Cancelled)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_timeout(
+    start_time: Instant,
+    max_connection_time: u64,
+    deadline: Option<Instant>,
+) -> Result<(), HttpRequestError> {
+    let elapsed = start_time.elapsed();
+
+    if max_connection_time > 0 && elapsed.as_millis() > u128::from(max_connection_time) {
+        return Err(HttpRequestError::TimeOut(elapsed));
+    }
+
+    if let Some(deadline) = deadline {
+        if Instant::now() >= deadline {
+            return Err(HttpRequestError::TimeOut(elapsed));
+        }
+    }
+
+    Ok(())
+}
+
+/// The URL of the proxy which the request should be sent through, unless the host is excluded by the `no_proxy` option.
+fn proxy_url(url: &Url, options: &HttpRequestOptions) -> Result<Option<Url>, HttpRequestError> {
+    let proxy = match options.proxy.as_ref() {
+        Some(proxy) => Url::parse(proxy)?,
+        None => return Ok(None),
+    };
+
+    if proxy.scheme() != "http" || proxy.host_str().is_none() {
+        return Err(HttpRequestError::Other("Only HTTP proxies are supported."));
+    }
+
+    let host = url.host_str().unwrap_or_default();
+
+    if is_no_proxy(host, &options.no_proxy) {
+        Ok(None)
+    } else {
+        Ok(Some(proxy))
+    }
+}
+
+fn is_no_proxy(host: &str, no_proxy: &[String]) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    no_proxy.iter().any(|entry| {
+        let entry = entry.trim().trim_start_matches('[').trim_end_matches(']');
+
+        if entry == "*" {
+            true
+        } else if entry.starts_with('.') {
+            host.to_ascii_lowercase().ends_with(&entry.to_ascii_lowercase())
+        } else {
+            host.eq_ignore_ascii_case(entry)
+        }
+    })
+}
+
+#[inline]
+fn is_timeout_error(error: &io::Error) -> bool {
+    matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+/// Whether a host is `localhost` or an IP address which is private, loopback, link-local, broadcast, documentation-only or unspecified, i.e. one which the `allow_local` option rejects. A domain is not resolved.
+pub fn is_local_host<S: AsRef<str>>(host: &Host<S>) -> bool {
+    match host {
+        Host::Ipv4(ipv4) => is_local_ipv4(*ipv4),
+        Host::Ipv6(ipv6) => is_local_ipv6(ipv6),
+        Host::Domain(domain) => domain.as_ref().eq_ignore_ascii_case("localhost"),
+    }
+}
+
+/// Whether the host of a URL is local. See `is_local_host`. A URL without a host is not local.
+#[inline]
+pub fn is_local_url(url: &Url) -> bool {
+    url.host().is_some_and(|host| is_local_host(&host))
+}
+
+#[inline]
+fn is_local_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ipv4) => is_local_ipv4(*ipv4),
+        IpAddr::V6(ipv6) => is_local_ipv6(ipv6),
     }
 }
 
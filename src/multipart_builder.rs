@@ -0,0 +1,147 @@
+use std::hash::Hash;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, process};
+
+use mime::Mime;
+
+use crate::{HttpRequestBody, MultipartPart};
+
+static BOUNDARY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Build a `multipart/form-data` body (`HttpRequestBody::Multipart`).
+#[derive(Debug, Clone)]
+pub struct MultipartBuilder {
+    boundary: String,
+    parts: Vec<MultipartPart>,
+}
+
+impl MultipartBuilder {
+    #[inline]
+    pub fn new() -> MultipartBuilder {
+        let nanos =
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+
+        let counter = BOUNDARY_COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        MultipartBuilder {
+            boundary: format!(
+                "----EasyHttpRequestBoundary{:x}{:x}{:x}",
+                nanos,
+                process::id(),
+                counter
+            ),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Use a specific boundary instead of a generated one.
+    #[inline]
+    pub fn boundary<S: Into<String>>(mut self, boundary: S) -> Self {
+        self.boundary = boundary.into();
+        self
+    }
+
+    /// Add a text field.
+    #[inline]
+    pub fn text<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.parts.push(MultipartPart {
+            name: name.into(),
+            filename: None,
+            content_type: None,
+            body: value.into().into_bytes(),
+        });
+        self
+    }
+
+    /// Add a file field. If `content_type` is `None`, it is guessed from the extension of `filename`.
+    #[inline]
+    pub fn file<N: Into<String>, F: Into<String>>(
+        mut self,
+        name: N,
+        filename: F,
+        content_type: Option<Mime>,
+        body: Vec<u8>,
+    ) -> Self {
+        let filename = filename.into();
+
+        let content_type = content_type.unwrap_or_else(|| mime_from_filename(&filename));
+
+        self.parts.push(MultipartPart {
+            name: name.into(),
+            filename: Some(filename),
+            content_type: Some(content_type),
+            body,
+        });
+        self
+    }
+
+    /// Add a file field with the content of a file. The file name and the `Content-Type` come from the path.
+    pub fn file_from_path<N: Into<String>, P: AsRef<Path>>(
+        self,
+        name: N,
+        path: P,
+    ) -> Result<Self, io::Error> {
+        let path = path.as_ref();
+
+        let body = fs::read(path)?;
+
+        let filename =
+            path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+        Ok(self.file(name, filename, None, body))
+    }
+
+    #[inline]
+    pub fn build<BK: Eq + Hash + AsRef<str>, BV: AsRef<str>>(self) -> HttpRequestBody<BK, BV> {
+        HttpRequestBody::Multipart {
+            boundary: self.boundary,
+            parts: self.parts,
+        }
+    }
+}
+
+impl Default for MultipartBuilder {
+    #[inline]
+    fn default() -> Self {
+        MultipartBuilder::new()
+    }
+}
+
+fn mime_from_filename(filename: &str) -> Mime {
+    let extension = match filename.rfind('.') {
+        Some(index) => filename[index + 1..].to_ascii_lowercase(),
+        None => return mime::APPLICATION_OCTET_STREAM,
+    };
+
+    let content_type = match extension.as_str() {
+        "txt" => "text/plain",
+        "htm" | "html" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "bmp" => "image/bmp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => return mime::APPLICATION_OCTET_STREAM,
+    };
+
+    content_type.parse().unwrap()
+}
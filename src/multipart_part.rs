@@ -0,0 +1,51 @@
+use mime::Mime;
+
+/// A part of a `multipart/form-data` body. See `MultipartBuilder`.
+#[derive(Debug, Clone)]
+pub struct MultipartPart {
+    pub name: String,
+    /// The file name of a file field.
+    pub filename: Option<String>,
+    /// The `Content-Type` of the part. A part without it is treated as `text/plain` by the server.
+    pub content_type: Option<Mime>,
+    pub body: Vec<u8>,
+}
+
+/// Encode the parts of a `multipart/form-data` body separated by `boundary`.
+pub(crate) fn encode_multipart(boundary: &str, parts: &[MultipartPart]) -> Vec<u8> {
+    let mut body = Vec::new();
+
+    for part in parts {
+        body.extend_from_slice(b"--");
+        body.extend_from_slice(boundary.as_bytes());
+        body.extend_from_slice(b"\r\nContent-Disposition: form-data; name=\"");
+        body.extend_from_slice(escape_quoted(&part.name).as_bytes());
+        body.push(b'"');
+
+        if let Some(filename) = part.filename.as_ref() {
+            body.extend_from_slice(b"; filename=\"");
+            body.extend_from_slice(escape_quoted(filename).as_bytes());
+            body.push(b'"');
+        }
+
+        if let Some(content_type) = part.content_type.as_ref() {
+            body.extend_from_slice(b"\r\nContent-Type: ");
+            body.extend_from_slice(content_type.as_ref().as_bytes());
+        }
+
+        body.extend_from_slice(b"\r\n\r\n");
+        body.extend_from_slice(&part.body);
+        body.extend_from_slice(b"\r\n");
+    }
+
+    body.extend_from_slice(b"--");
+    body.extend_from_slice(boundary.as_bytes());
+    body.extend_from_slice(b"--\r\n");
+
+    body
+}
+
+/// Percent-encode the characters which cannot be in a quoted field name or file name, as browsers do.
+fn escape_quoted(s: &str) -> String {
+    s.replace('"', "%22").replace('\r', "%0D").replace('\n', "%0A")
+}
@@ -0,0 +1,50 @@
+use std::io::{self, Read};
+
+/// Counts the bytes read from the inner reader.
+pub(crate) struct CountingReader<R: Read> {
+    inner: R,
+    count: usize,
+    is_eof: bool,
+}
+
+impl<R: Read> CountingReader<R> {
+    #[inline]
+    pub(crate) fn new(inner: R) -> CountingReader<R> {
+        CountingReader {
+            inner,
+            count: 0,
+            is_eof: false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Whether the inner reader has reached its end.
+    #[inline]
+    pub(crate) fn is_eof(&self) -> bool {
+        self.is_eof
+    }
+
+    #[inline]
+    pub(crate) fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let c = self.inner.read(buf)?;
+
+        if c == 0 && !buf.is_empty() {
+            self.is_eof = true;
+        }
+
+        self.count += c;
+
+        Ok(c)
+    }
+}
@@ -0,0 +1,12 @@
+use url::Url;
+
+/// What to do with a redirection. See `HttpRequestOptions::redirect_handler`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedirectAction {
+    /// Follow the redirection to this URL.
+    Follow(Url),
+    /// Do not follow the redirection and return the 3xx response.
+    Stop,
+    /// Fail with a `RedirectError`.
+    Error,
+}
@@ -0,0 +1,8 @@
+/// How a redirection of a status code is followed. See `HttpRequestOptions::redirect_behavior`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RedirectMethodPolicy {
+    /// Send the same method and body to the new URL.
+    Keep,
+    /// Send a `GET` request without the body to the new URL.
+    Get,
+}
@@ -0,0 +1,72 @@
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use crate::{HttpRequestError, HttpResponse};
+
+#[derive(Default)]
+struct State {
+    result: Option<Result<HttpResponse, HttpRequestError>>,
+    waker: Option<Waker>,
+}
+
+/// The future of a request which is sent (blocking) on a thread of its own. See `HttpRequest::send_async`.
+pub struct SendFuture {
+    state: Arc<Mutex<State>>,
+}
+
+impl SendFuture {
+    pub(crate) fn spawn<F>(f: F) -> SendFuture
+    where
+        F: FnOnce() -> Result<HttpResponse, HttpRequestError> + Send + 'static, {
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let thread_state = state.clone();
+
+        thread::spawn(move || {
+            // a panic (e.g. of a user callback) must still complete the future
+            let result = panic::catch_unwind(AssertUnwindSafe(f))
+                .unwrap_or(Err(HttpRequestError::Other("The request has panicked.")));
+
+            let mut state = thread_state.lock().unwrap();
+
+            state.result = Some(result);
+
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+
+        SendFuture {
+            state,
+        }
+    }
+}
+
+impl Future for SendFuture {
+    type Output = Result<HttpResponse, HttpRequestError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Debug for SendFuture {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("SendFuture").finish_non_exhaustive()
+    }
+}
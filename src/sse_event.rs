@@ -0,0 +1,14 @@
+use std::time::Duration;
+
+/// An event of a `text/event-stream` (server-sent events) body. See `HttpRequest::send_event_stream`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `event` field. It is `message` if the event does not have one.
+    pub event: String,
+    /// The `data` fields, joined by `\n`.
+    pub data: String,
+    /// The last `id` field, which carries over to the following events until another one is received.
+    pub id: Option<String>,
+    /// The `retry` field, which is the reconnection time the server asks for.
+    pub retry: Option<Duration>,
+}
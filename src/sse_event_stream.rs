@@ -0,0 +1,195 @@
+use std::fmt::{self, Debug, Formatter};
+use std::time::Duration;
+
+use crate::{HttpRequestError, HttpResponseHead, HttpResponseReader, SseEvent, BUFFER_SIZE};
+
+const DEFAULT_MAX_EVENT_SIZE: usize = 1024 * 1024;
+
+/// The events of a `text/event-stream` body, parsed while they are being received. The size and time limits of the options (including `idle_read_timeout` between events) are enforced while reading. See `HttpRequest::send_event_stream`.
+///
+/// Lines end with `\n`, `\r\n` or `\r`. A line or the data of an event larger than `max_event_size` is a `TooLarge` error, even if `max_response_body_size` is disabled. The iteration ends when the server closes the stream, or after the first error.
+pub struct SseEventStream {
+    head: HttpResponseHead,
+    reader: HttpResponseReader,
+    buffer: Vec<u8>,
+    /// The last line ended with `\r`, so a `\n` right after it belongs to the same terminator.
+    skip_line_feed: bool,
+    max_event_size: usize,
+    last_event_id: Option<String>,
+    finished: bool,
+}
+
+impl SseEventStream {
+    #[inline]
+    pub(crate) fn new(head: HttpResponseHead, reader: HttpResponseReader) -> SseEventStream {
+        SseEventStream {
+            head,
+            reader,
+            buffer: Vec::new(),
+            skip_line_feed: false,
+            max_event_size: DEFAULT_MAX_EVENT_SIZE,
+            last_event_id: None,
+            finished: false,
+        }
+    }
+
+    /// The status and the headers of the response.
+    #[inline]
+    pub fn head(&self) -> &HttpResponseHead {
+        &self.head
+    }
+
+    /// Set the size limit in bytes of a line, and of the data of an event. The default value is 1 MiB.
+    #[inline]
+    pub fn max_event_size(mut self, max_event_size: usize) -> Self {
+        self.max_event_size = max_event_size;
+
+        self
+    }
+
+    /// Read a line without its terminator, or `None` at the end of the body.
+    fn read_line(&mut self) -> Result<Option<String>, HttpRequestError> {
+        let mut searched = 0;
+
+        loop {
+            if self.skip_line_feed && !self.buffer.is_empty() {
+                if self.buffer[0] == b'\n' {
+                    self.buffer.remove(0);
+                }
+
+                self.skip_line_feed = false;
+            }
+
+            if let Some(index) =
+                self.buffer[searched..].iter().position(|&b| b == b'\n' || b == b'\r')
+            {
+                let index = searched + index;
+
+                let mut line: Vec<u8> = self.buffer.drain(..=index).collect();
+
+                self.skip_line_feed = line.pop() == Some(b'\r');
+
+                return Ok(Some(String::from_utf8_lossy(&line).into_owned()));
+            }
+
+            if self.buffer.len() > self.max_event_size {
+                return Err(HttpRequestError::TooLarge {
+                    size: self.buffer.len(),
+                    limit: self.max_event_size,
+                });
+            }
+
+            searched = self.buffer.len();
+
+            let mut buffer = [0u8; BUFFER_SIZE];
+
+            match self.reader.read_inner(&mut buffer)? {
+                // an incomplete line (and event) at the end is dropped
+                0 => return Ok(None),
+                c => self.buffer.extend_from_slice(&buffer[..c]),
+            }
+        }
+    }
+
+    fn read_event(&mut self) -> Result<Option<SseEvent>, HttpRequestError> {
+        let mut event = None;
+        let mut data = String::new();
+        let mut has_data = false;
+        let mut retry = None;
+
+        while let Some(line) = self.read_line()? {
+            if line.is_empty() {
+                if has_data {
+                    // the last data line does not end with `\n`
+                    return Ok(Some(SseEvent {
+                        event: event.unwrap_or_else(|| "message".to_string()),
+                        data,
+                        id: self.last_event_id.clone(),
+                        retry,
+                    }));
+                }
+
+                // an event without data is not dispatched
+                event = None;
+                retry = None;
+
+                continue;
+            }
+
+            // a comment, often sent to keep the connection alive
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.find(':') {
+                Some(index) => {
+                    let value = &line[index + 1..];
+
+                    (&line[..index], value.strip_prefix(' ').unwrap_or(value))
+                }
+                None => (line.as_str(), ""),
+            };
+
+            match field {
+                "event" => event = Some(value.to_string()),
+                "data" => {
+                    if has_data {
+                        data.push('\n');
+                    }
+
+                    data.push_str(value);
+
+                    if data.len() > self.max_event_size {
+                        return Err(HttpRequestError::TooLarge {
+                            size: data.len(),
+                            limit: self.max_event_size,
+                        });
+                    }
+
+                    has_data = true;
+                }
+                "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+                "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                    retry = value.parse().ok().map(Duration::from_millis);
+                }
+                _ => (),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Iterator for SseEventStream {
+    type Item = Result<SseEvent, HttpRequestError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        match self.read_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.finished = true;
+
+                None
+            }
+            Err(err) => {
+                self.finished = true;
+
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl Debug for SseEventStream {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("SseEventStream")
+            .field("head", &self.head)
+            .field("last_event_id", &self.last_event_id)
+            .finish_non_exhaustive()
+    }
+}
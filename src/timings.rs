@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+/// How long each phase of a request took. See the `collect_timings` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    /// The time of resolving the host. `None` if the host is an IP address or has been overridden.
+    pub dns: Option<Duration>,
+    /// The time of establishing the TCP (or Unix socket) connection.
+    pub connect: Duration,
+    /// The time of the TLS handshake. `None` for plain HTTP.
+    pub tls: Option<Duration>,
+    /// The time from the start of the request to the arrival of the response headers.
+    pub time_to_first_byte: Duration,
+    /// The time from the start of the request to the end of the response body.
+    pub total: Duration,
+}
+
+/// The timings recorded by the connector.
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionTimings {
+    pub(crate) dns: Option<Duration>,
+    pub(crate) connect: Duration,
+    pub(crate) tls: Option<Duration>,
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "async")]
+
+extern crate easy_http_request;
+
+mod common;
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+
+use easy_http_request::{DefaultHttpRequest, HttpRequestError};
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn test_send_async() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b"async"));
+
+    let response =
+        block_on(DefaultHttpRequest::get_from_url_str(&base).unwrap().send_async()).unwrap();
+
+    assert_eq!(200, response.status_code);
+    assert_eq!(b"async".to_vec(), response.body);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_async_panic() {
+    let (base, server) = common::serve(1, |_, _| common::response("401 Unauthorized", &[], b""));
+
+    let future = DefaultHttpRequest::get_from_url_str(&base)
+        .unwrap()
+        .on_unauthorized(|| panic!("no credentials"))
+        .send_async();
+
+    match block_on(future) {
+        Err(HttpRequestError::Other(_)) => (),
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
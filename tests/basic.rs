@@ -1,5 +1,7 @@
 extern crate easy_http_request;
 
+mod common;
+
 use easy_http_request::DefaultHttpRequest;
 
 #[test]
@@ -16,3 +18,19 @@ fn test_get() {
     DefaultHttpRequest::get_from_url_str("https://magiclen.org").unwrap().send().unwrap();
     DefaultHttpRequest::get_from_url_str("https://magiclen.org").unwrap().send_preserved().unwrap();
 }
+
+#[test]
+fn test_send_reusable() {
+    let (base, server) =
+        common::serve(2, |i, _| common::response("200 OK", &[], format!("{}", i).as_bytes()));
+
+    let request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    let (response, request) = request.send_reusable().unwrap();
+    assert_eq!(b"0", response.body.as_slice());
+
+    let (response, _) = request.send_reusable().unwrap();
+    assert_eq!(b"1", response.body.as_slice());
+
+    server.join().unwrap();
+}
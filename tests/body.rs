@@ -0,0 +1,482 @@
+extern crate easy_http_request;
+extern crate flate2;
+
+mod common;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+use easy_http_request::url::{form_urlencoded, Url};
+use easy_http_request::{
+    mime, DefaultHttpRequest, HttpRequestBody, MultipartBuilder, StaticHttpRequest,
+};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+#[test]
+fn test_from_file() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/upload.txt");
+    let content = fs::read(path).unwrap();
+
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    request.body = Some(HttpRequestBody::from_file(path, mime::TEXT_PLAIN).unwrap());
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some(content.len().to_string().as_str()), requests[0].header("Content-Length"));
+    assert_eq!(Some("text/plain"), requests[0].header("Content-Type"));
+    assert_eq!(content, requests[0].body);
+}
+
+#[test]
+fn test_form_url_encoded() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    let mut form = HashMap::new();
+    form.insert("name".to_string(), "magiclen".to_string());
+    form.insert("lang".to_string(), "rust".to_string());
+
+    request.body = Some(HttpRequestBody::FormURLEncoded(form.clone()));
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("application/x-www-form-urlencoded"), requests[0].header("Content-Type"));
+
+    let received: HashMap<String, String> =
+        form_urlencoded::parse(&requests[0].body).into_owned().collect();
+
+    assert_eq!(form, received);
+}
+
+#[test]
+fn test_form_url_encoded_special_characters() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    let mut form = HashMap::new();
+    form.insert("a&b=c".to_string(), "1+1 = 2 & 3".to_string());
+    form.insert("unicode".to_string(), "中文 café".to_string());
+
+    request.body = Some(HttpRequestBody::FormURLEncoded(form.clone()));
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    let body = String::from_utf8(requests[0].body.clone()).unwrap();
+
+    // spaces are encoded as `+`, and `+` itself is percent-encoded
+    assert!(body.contains("1%2B1+%3D+2+%26+3"));
+    assert!(body.contains("a%26b%3Dc="));
+
+    let received: HashMap<String, String> =
+        form_urlencoded::parse(body.as_bytes()).into_owned().collect();
+
+    assert_eq!(form, received);
+}
+
+#[test]
+fn test_form_url_encoded_custom_content_type() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    let mut headers = HashMap::new();
+    headers.insert(
+        "Content-Type".to_string(),
+        "application/x-www-form-urlencoded; charset=UTF-8".to_string(),
+    );
+    request.headers = Some(headers);
+
+    let mut form = HashMap::new();
+    form.insert("name".to_string(), "magiclen".to_string());
+    request.body = Some(HttpRequestBody::FormURLEncoded(form));
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(
+        Some("application/x-www-form-urlencoded; charset=UTF-8"),
+        requests[0].header("Content-Type")
+    );
+    assert_eq!(b"name=magiclen", requests[0].body.as_slice());
+}
+
+#[test]
+fn test_custom_content_type() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    let mut headers = HashMap::new();
+    headers.insert("content-type".to_string(), "application/vnd.api+json".to_string());
+    request.headers = Some(headers);
+
+    request.body = Some(HttpRequestBody::Text {
+        content_type: mime::APPLICATION_JSON,
+        body: "{}".to_string(),
+    });
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("application/vnd.api+json"), requests[0].header("Content-Type"));
+    assert_eq!(
+        1,
+        requests[0].headers.iter().filter(|(k, _)| k.eq_ignore_ascii_case("Content-Type")).count()
+    );
+}
+
+#[test]
+fn test_raw() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    request.body = Some(HttpRequestBody::Raw(b"\x89PNG".to_vec()));
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(None, requests[0].header("Content-Type"));
+    assert_eq!(Some("4"), requests[0].header("Content-Length"));
+    assert_eq!(b"\x89PNG", requests[0].body.as_slice());
+}
+
+#[test]
+fn test_post_text_and_form() {
+    let (base, server) = common::serve(3, |_, _| common::response("200 OK", &[], b""));
+
+    let url = Url::parse(&base).unwrap();
+
+    StaticHttpRequest::post_text(url.clone(), mime::TEXT_PLAIN, "hello").send().unwrap();
+
+    let mut form = HashMap::new();
+    form.insert("a", "1");
+
+    StaticHttpRequest::put_form(url.clone(), form.clone()).send().unwrap();
+
+    StaticHttpRequest::delete_form(url, form).send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("POST", requests[0].method);
+    assert_eq!(b"hello", requests[0].body.as_slice());
+    assert_eq!("PUT", requests[1].method);
+    assert_eq!(b"a=1", requests[1].body.as_slice());
+    assert_eq!("DELETE", requests[2].method);
+    assert_eq!(b"a=1", requests[2].body.as_slice());
+}
+
+#[test]
+fn test_multipart() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/upload.txt");
+    let content = fs::read(path).unwrap();
+
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    request.body = Some(
+        MultipartBuilder::new()
+            .boundary("BOUNDARY")
+            .text("name", "magiclen")
+            .file("image", "a.PNG", None, vec![0x89, b'P', b'N', b'G'])
+            .file_from_path("upload", path)
+            .unwrap()
+            .build(),
+    );
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    let mut expected = b"--BOUNDARY\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nmagiclen\r\n--BOUNDARY\r\nContent-Disposition: form-data; name=\"image\"; filename=\"a.PNG\"\r\nContent-Type: image/png\r\n\r\n\x89PNG\r\n--BOUNDARY\r\nContent-Disposition: form-data; name=\"upload\"; filename=\"upload.txt\"\r\nContent-Type: text/plain\r\n\r\n".to_vec();
+    expected.extend_from_slice(&content);
+    expected.extend_from_slice(b"\r\n--BOUNDARY--\r\n");
+
+    assert_eq!(Some("multipart/form-data; boundary=BOUNDARY"), requests[0].header("Content-Type"));
+    assert_eq!(expected, requests[0].body);
+}
+
+#[test]
+fn test_no_body() {
+    let (base, server) = common::serve(2, |_, _| common::response("200 OK", &[], b""));
+
+    DefaultHttpRequest::post_from_url_str(&base).unwrap().send().unwrap();
+    DefaultHttpRequest::put_from_url_str(&base).unwrap().send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    // some servers answer 411 Length Required without it
+    assert_eq!(Some("0"), requests[0].header("Content-Length"));
+    assert_eq!(Some("0"), requests[1].header("Content-Length"));
+}
+
+#[test]
+fn test_get_and_delete_body() {
+    let (base, server) =
+        common::serve(2, |_, request| common::response("200 OK", &[], &request.body));
+
+    for mut request in [
+        DefaultHttpRequest::get_from_url_str(&base).unwrap(),
+        DefaultHttpRequest::delete_from_url_str(&base).unwrap(),
+    ] {
+        request.body = Some(HttpRequestBody::json_str(r#"{"query":{"match_all":{}}}"#));
+
+        assert_eq!(br#"{"query":{"match_all":{}}}"#.to_vec(), request.send().unwrap().body);
+    }
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("GET", requests[0].method);
+    assert_eq!("DELETE", requests[1].method);
+    assert_eq!(Some("application/json"), requests[0].header("Content-Type"));
+}
+
+#[test]
+fn test_expect_continue() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let mut received = Vec::new();
+
+        // accepted: the server asks for the body
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let head = read_head(&mut reader);
+        stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").unwrap();
+
+        let mut body = vec![0; 7];
+        reader.read_exact(&mut body).unwrap();
+        stream.write_all(&common::response("201 Created", &[], b"done")).unwrap();
+
+        received.push((head, body));
+
+        // rejected: the server answers before the body is sent
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let head = read_head(&mut reader);
+        stream.write_all(&common::response("413 Payload Too Large", &[], b"")).unwrap();
+
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body).unwrap();
+
+        received.push((head, body));
+
+        received
+    });
+
+    for expected in [201, 413] {
+        let mut request = DefaultHttpRequest::put_from_url_str(&base).unwrap();
+
+        request.body = Some(HttpRequestBody::Raw(b"payload".to_vec()));
+        request.options.use_expect_continue = true;
+
+        let response = request.send().unwrap();
+
+        assert_eq!(expected, response.status_code);
+    }
+
+    let received = server.join().unwrap();
+
+    assert!(received[0].0.contains("Expect: 100-continue\r\n"));
+    assert_eq!(b"payload".to_vec(), received[0].1);
+    assert!(received[1].1.is_empty());
+}
+
+#[test]
+fn test_expect_continue_ignored() {
+    // the server never sends `100 Continue` and waits for the body
+    let (base, server) = common::serve(1, |_, _| common::response("201 Created", &[], b"done"));
+
+    let mut request = DefaultHttpRequest::put_from_url_str(&base).unwrap();
+
+    request.body = Some(HttpRequestBody::Raw(b"payload".to_vec()));
+    request.options.use_expect_continue = true;
+    request.options.idle_read_timeout = Some(Duration::from_millis(400));
+
+    let response = request.send().unwrap();
+
+    assert_eq!(201, response.status_code);
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("100-continue"), requests[0].header("Expect"));
+    assert_eq!(b"payload", requests[0].body.as_slice());
+}
+
+fn read_head<R: BufRead>(reader: &mut R) -> String {
+    let mut head = String::new();
+
+    loop {
+        let length = head.len();
+
+        reader.read_line(&mut head).unwrap();
+
+        if head[length..] == *"\r\n" {
+            return head;
+        }
+    }
+}
+
+#[test]
+fn test_pre_compressed() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(br#"{"level":"info"}"#).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    let mut headers = HashMap::new();
+    headers.insert("Content-Encoding".to_string(), "gzip".to_string());
+
+    request.headers = Some(headers);
+    request.body = Some(HttpRequestBody::Binary {
+        content_type: mime::APPLICATION_JSON,
+        body: compressed.clone(),
+    });
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    // the body is sent as it is, with the encoding set by the caller
+    assert_eq!(Some("gzip"), requests[0].header("Content-Encoding"));
+    assert_eq!(Some(compressed.len().to_string().as_str()), requests[0].header("Content-Length"));
+    assert_eq!(compressed, requests[0].body);
+}
+
+#[test]
+fn test_form_url_encoded_ordered() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    request.body = Some(HttpRequestBody::FormURLEncodedOrdered(vec![
+        ("oauth_nonce".to_string(), "abc".to_string()),
+        ("a b".to_string(), "1+1".to_string()),
+        ("oauth_nonce".to_string(), "def".to_string()),
+    ]));
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("application/x-www-form-urlencoded"), requests[0].header("Content-Type"));
+    assert_eq!(b"oauth_nonce=abc&a+b=1%2B1&oauth_nonce=def".to_vec(), requests[0].body);
+}
+
+#[test]
+fn test_body_str() {
+    let (base, server) = common::serve(2, |i, _| {
+        match i {
+            0 => common::response("200 OK", &[], "héllo".as_bytes()),
+            _ => common::response("200 OK", &[], b"bad \xff"),
+        }
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert_eq!(Ok("héllo"), response.body_str());
+    assert!(matches!(response.body_str_lossy(), Cow::Borrowed("héllo")));
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert!(response.body_str().is_err());
+    assert_eq!("bad \u{fffd}", response.body_str_lossy());
+    assert_eq!(b"bad \xff".to_vec(), response.into_body());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_save_body_to_file() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b"saved"));
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    let path = std::env::temp_dir()
+        .join(format!("easy-http-request-save-body-to-file-{}", std::process::id()));
+
+    response.save_body_to_file(&path).unwrap();
+
+    assert_eq!(b"saved".to_vec(), std::fs::read(&path).unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_body_sizes() {
+    let (base, server) = common::serve(3, |i, _| {
+        match i {
+            0 => common::response("200 OK", &[], b"abc"),
+            1 => common::response("303 See Other", &[("Location", "/done")], b""),
+            _ => common::response("200 OK", &[], b"done"),
+        }
+    });
+
+    let url = Url::parse(&base).unwrap();
+
+    let response =
+        StaticHttpRequest::post_text(url.clone(), mime::TEXT_PLAIN, "hello").send().unwrap();
+
+    assert_eq!(5, response.request_body_size);
+    assert_eq!(3, response.response_body_size);
+
+    // the body is only sent once, before the `303` redirection drops it
+    let response = StaticHttpRequest::post_text(url, mime::TEXT_PLAIN, "hello").send().unwrap();
+
+    assert_eq!(5, response.request_body_size);
+    assert_eq!(4, response.response_body_size);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_request_body_size_resent() {
+    let (base, server) = common::serve(3, |i, _| {
+        match i {
+            0 => common::response("307 Temporary Redirect", &[("Location", "/a")], b""),
+            1 => common::response("401 Unauthorized", &[], b""),
+            _ => common::response("200 OK", &[], b""),
+        }
+    });
+
+    let response =
+        StaticHttpRequest::post_text(Url::parse(&base).unwrap(), mime::TEXT_PLAIN, "hello")
+            .on_unauthorized(|| "Bearer token".to_string())
+            .send()
+            .unwrap();
+
+    // the body is sent again for the redirection and for the retry
+    assert_eq!(15, response.request_body_size);
+
+    server.join().unwrap();
+}
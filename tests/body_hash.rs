@@ -0,0 +1,25 @@
+#![cfg(feature = "body-hash")]
+
+extern crate easy_http_request;
+
+mod common;
+
+use easy_http_request::{BodyHashAlgorithm, DefaultHttpRequest};
+
+#[test]
+fn test_body_hash() {
+    let (base, server) = common::serve(2, |_, _| common::response("200 OK", &[], b"abc"));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    assert_eq!(None, request.send_preserved().unwrap().body_hash);
+
+    request.options.body_hash_algorithm = Some(BodyHashAlgorithm::Sha256);
+
+    assert_eq!(
+        Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        request.send().unwrap().body_hash.as_deref()
+    );
+
+    server.join().unwrap();
+}
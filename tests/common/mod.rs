@@ -0,0 +1,183 @@
+#![allow(dead_code)]
+
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::Once;
+use std::thread::{self, JoinHandle};
+
+use easy_http_request::hyper_native_tls::native_tls::{Identity, TlsAcceptor};
+
+/// A request received by the test server.
+#[derive(Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+/// Start a server on a random local port. It accepts `count` connections and answers the request
+/// on each of them with the bytes produced by `handler`.
+///
+/// Returns the base URL (without a trailing slash) and a handle which yields the recorded requests.
+pub fn serve<F>(count: usize, handler: F) -> (String, JoinHandle<Vec<RecordedRequest>>)
+where
+    F: Fn(usize, &RecordedRequest) -> Vec<u8> + Send + 'static, {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::with_capacity(count);
+
+        for i in 0..count {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let request = read_request(&mut stream);
+
+            // the client may abort reading early, so write errors are ignored
+            let _ = stream.write_all(&handler(i, &request));
+            let _ = stream.flush();
+
+            requests.push(request);
+        }
+
+        requests
+    });
+
+    (base, handle)
+}
+
+/// Like `serve`, but over TLS with the certificate of `localhost` in `tests/data/tls`, which is signed by
+/// the test CA. Call `trust_test_ca` before connecting to it.
+///
+/// Returns the base URL (`https://localhost:<port>`) and a handle which yields the recorded requests.
+pub fn serve_tls<F>(count: usize, handler: F) -> (String, JoinHandle<Vec<RecordedRequest>>)
+where
+    F: Fn(usize, &RecordedRequest) -> Vec<u8> + Send + 'static, {
+    let identity = Identity::from_pkcs8(
+        include_bytes!("../data/tls/localhost.pem"),
+        include_bytes!("../data/tls/localhost-key.pem"),
+    )
+    .unwrap();
+    let acceptor = TlsAcceptor::new(identity).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("https://localhost:{}", listener.local_addr().unwrap().port());
+
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::with_capacity(count);
+
+        for i in 0..count {
+            let (stream, _) = listener.accept().unwrap();
+
+            let mut stream = acceptor.accept(stream).unwrap();
+
+            let request = read_request(&mut stream);
+
+            let _ = stream.write_all(&handler(i, &request));
+            let _ = stream.flush();
+
+            requests.push(request);
+        }
+
+        requests
+    });
+
+    (base, handle)
+}
+
+/// Make OpenSSL trust only the test CA in `tests/data/tls`, through `SSL_CERT_FILE`. This affects the
+/// whole test process, so it is only for test files which do not connect to real HTTPS servers.
+pub fn trust_test_ca() {
+    static TRUST: Once = Once::new();
+
+    TRUST.call_once(|| {
+        env::set_var(
+            "SSL_CERT_FILE",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/tls/ca.pem"),
+        );
+    });
+}
+
+/// Build a complete response with a `Content-Length` header and `Connection: close`.
+pub fn response(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut response = format!("HTTP/1.1 {}\r\n", status);
+
+    for (k, v) in headers {
+        response.push_str(&format!("{}: {}\r\n", k, v));
+    }
+
+    response.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", body.len()));
+
+    let mut response = response.into_bytes();
+
+    response.extend_from_slice(body);
+
+    response
+}
+
+pub fn read_request<S: Read>(stream: &mut S) -> RecordedRequest {
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+
+    let mut request_line = line.trim_end().splitn(3, ' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+
+    loop {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+
+        let header = line.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(index) = header.find(':') {
+            headers.push((header[..index].to_string(), header[index + 1..].trim().to_string()));
+        }
+    }
+
+    let mut request = RecordedRequest {
+        method,
+        target,
+        headers,
+        body: Vec::new(),
+    };
+
+    if let Some(length) = request.header("Content-Length") {
+        let mut body = vec![0; length.parse().unwrap()];
+        reader.read_exact(&mut body).unwrap();
+        request.body = body;
+    } else if request.header("Transfer-Encoding").is_some_and(|v| v.contains("chunked")) {
+        loop {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+
+            let size = usize::from_str_radix(line.trim(), 16).unwrap();
+
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk).unwrap();
+
+            if size == 0 {
+                break;
+            }
+
+            request.body.extend_from_slice(&chunk[..size]);
+        }
+    }
+
+    request
+}
@@ -0,0 +1,325 @@
+extern crate easy_http_request;
+
+mod common;
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use easy_http_request::url::Url;
+use easy_http_request::{mime, DefaultHttpRequest, HttpRequestBody, HttpRequestError};
+
+#[test]
+fn test_resolve() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let port = Url::parse(&base).unwrap().port().unwrap();
+
+    let mut request =
+        DefaultHttpRequest::get_from_url_str(format!("http://backend.test:{}/", port)).unwrap();
+
+    request.options.resolve.push(("backend.test".to_string(), IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some(format!("backend.test:{}", port).as_str()), requests[0].header("Host"));
+}
+
+#[test]
+fn test_host_override() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.host_override = Some("virtual.test".to_string());
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("virtual.test"), requests[0].header("Host"));
+    assert_eq!(
+        1,
+        requests[0].headers.iter().filter(|(k, _)| k.eq_ignore_ascii_case("Host")).count()
+    );
+}
+
+/// Redirect to `localhost`, and then back to `127.0.0.1`.
+fn serve_cross_host_redirects() -> (String, JoinHandle<Vec<common::RecordedRequest>>) {
+    common::serve(3, |i, request| {
+        let port = request.header("Host").unwrap().rsplit(':').next().unwrap().to_string();
+
+        match i {
+            0 => {
+                let location = format!("http://localhost:{}/other", port);
+
+                common::response("302 Found", &[("Location", location.as_str())], b"")
+            }
+            1 => {
+                let location = format!("http://127.0.0.1:{}/back", port);
+
+                common::response("302 Found", &[("Location", location.as_str())], b"")
+            }
+            _ => common::response("200 OK", &[], b""),
+        }
+    })
+}
+
+#[test]
+fn test_host_override_redirect() {
+    let (base, server) = serve_cross_host_redirects();
+
+    let port = Url::parse(&base).unwrap().port().unwrap();
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.host_override = Some(format!("virtual.test:{}", port));
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    let localhost = format!("localhost:{}", port);
+    let virtual_host = format!("virtual.test:{}", port);
+
+    // the overridden host is not sent to another host
+    assert_eq!(Some(virtual_host.as_str()), requests[0].header("Host"));
+    assert_eq!(Some(localhost.as_str()), requests[1].header("Host"));
+    assert_eq!(Some(virtual_host.as_str()), requests[2].header("Host"));
+}
+
+#[test]
+fn test_host_header() {
+    let (base, server) = common::serve(3, |_, _| common::response("200 OK", &[], b""));
+
+    // the URL host is an IP literal
+    let port = Url::parse(&base).unwrap().port().unwrap();
+
+    DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    let mut headers = HashMap::new();
+    headers.insert("host".to_string(), "virtual.test".to_string());
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.headers = Some(headers.clone());
+
+    request.send().unwrap();
+
+    // also when the request is written by hand, as a `GET` request with a body is
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.headers = Some(headers);
+    request.body = Some(HttpRequestBody::Binary {
+        content_type: mime::APPLICATION_OCTET_STREAM,
+        body: b"a".to_vec(),
+    });
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some(format!("127.0.0.1:{}", port).as_str()), requests[0].header("Host"));
+
+    for request in &requests[1..] {
+        assert_eq!(Some("virtual.test"), request.header("Host"));
+        assert_eq!(
+            1,
+            request.headers.iter().filter(|(k, _)| k.eq_ignore_ascii_case("Host")).count()
+        );
+    }
+}
+
+#[test]
+fn test_host_header_redirect() {
+    let (base, server) = serve_cross_host_redirects();
+
+    let port = Url::parse(&base).unwrap().port().unwrap();
+
+    let virtual_host = format!("virtual.test:{}", port);
+
+    let mut headers = HashMap::new();
+    headers.insert("Host".to_string(), virtual_host.clone());
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.headers = Some(headers);
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    let localhost = format!("localhost:{}", port);
+
+    // the `Host` header is dropped for another host
+    assert_eq!(Some(virtual_host.as_str()), requests[0].header("Host"));
+    assert_eq!(Some(localhost.as_str()), requests[1].header("Host"));
+    assert_eq!(Some(virtual_host.as_str()), requests[2].header("Host"));
+}
+
+#[test]
+fn test_disable_keep_alive() {
+    let (base, server) = common::serve(2, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.disable_keep_alive = true;
+
+    request.send_preserved().unwrap();
+
+    let mut headers = HashMap::new();
+    headers.insert("Connection".to_string(), "keep-alive".to_string());
+    request.headers = Some(headers);
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("close"), requests[0].header("Connection"));
+    assert_eq!(Some("keep-alive"), requests[1].header("Connection"));
+}
+
+#[test]
+fn test_resolve_local_not_allow() {
+    let mut request = DefaultHttpRequest::get_safe_from_url_str("http://backend.test/").unwrap();
+
+    request.options.resolve.push(("backend.test".to_string(), IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+    match request.send() {
+        Err(HttpRequestError::LocalNotAllow) => (),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_connect() {
+    let (base, server) = common::serve(1, |_, _| {
+        b"HTTP/1.1 200 Connection Established\r\nProxy-Agent: test\r\n\r\n".to_vec()
+    });
+
+    let port = Url::parse(&base).unwrap().port().unwrap();
+
+    let mut request =
+        DefaultHttpRequest::connect_from_url_str(format!("http://example.test:{}", port)).unwrap();
+
+    request.options.resolve.push(("example.test".to_string(), IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+    let response = request.send().unwrap();
+
+    assert_eq!(200, response.status_code);
+    assert_eq!(Some("test"), response.header("Proxy-Agent"));
+    assert!(response.body.is_empty());
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("CONNECT", requests[0].method);
+    assert_eq!(format!("example.test:{}", port), requests[0].target);
+    assert_eq!(Some(format!("example.test:{}", port).as_str()), requests[0].header("Host"));
+}
+
+#[test]
+fn test_proxy() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b"proxied"));
+
+    let mut request = DefaultHttpRequest::get_from_url_str("http://example.test/path?a=1").unwrap();
+
+    request.options.proxy = Some(base);
+
+    let response = request.send().unwrap();
+
+    assert_eq!(b"proxied".to_vec(), response.body);
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("http://example.test/path?a=1", requests[0].target);
+}
+
+#[test]
+fn test_no_proxy() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let port = Url::parse(&base).unwrap().port().unwrap();
+
+    let mut request =
+        DefaultHttpRequest::get_from_url_str(format!("http://api.internal:{}/", port)).unwrap();
+
+    request.options.resolve.push(("api.internal".to_string(), IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    // nothing listens on this port, so the request only succeeds if the proxy is bypassed
+    request.options.proxy = Some("http://127.0.0.1:1".to_string());
+    request.options.no_proxy = vec!["example.test".to_string(), ".internal".to_string()];
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("/", requests[0].target);
+}
+
+#[test]
+fn test_remote_addr() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let port = Url::parse(&base).unwrap().port().unwrap();
+
+    let mut request =
+        DefaultHttpRequest::get_from_url_str(format!("http://backend.test:{}/", port)).unwrap();
+
+    request.options.resolve.push(("backend.test".to_string(), IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+    let response = request.send().unwrap();
+
+    assert_eq!(Some(SocketAddr::from((Ipv4Addr::LOCALHOST, port))), response.remote_addr);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_socket_options() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b"ok"));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.tcp_nodelay = true;
+    request.options.tcp_keepalive = Some(Duration::from_secs(30));
+
+    assert_eq!(b"ok".to_vec(), request.send().unwrap().body);
+
+    server.join().unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_local_address() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let (mut stream, addr) = listener.accept().unwrap();
+
+        common::read_request(&mut stream);
+
+        stream.write_all(&common::response("200 OK", &[], b"")).unwrap();
+
+        addr.ip()
+    });
+
+    // every address in 127.0.0.0/8 is a loopback address on Linux
+    let local_address = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.local_address = Some(local_address);
+
+    request.send_preserved().unwrap();
+
+    assert_eq!(local_address, server.join().unwrap());
+
+    // an address which is not on this host cannot be bound
+    request.options.local_address = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+
+    assert!(request.send().is_err());
+}
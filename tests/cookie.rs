@@ -0,0 +1,61 @@
+extern crate easy_http_request;
+
+mod common;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use easy_http_request::{Cookie, DefaultHttpRequest};
+
+#[test]
+fn test_parse() {
+    let cookie = Cookie::parse(
+        "id=a3fWa; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=2592000; Domain=.magiclen.org; \
+         Path=/docs; Secure; HttpOnly; SameSite=Lax",
+    )
+    .unwrap();
+
+    assert_eq!("id", cookie.name);
+    assert_eq!("a3fWa", cookie.value);
+    assert_eq!(Some("magiclen.org"), cookie.domain.as_deref());
+    assert_eq!(Some("/docs"), cookie.path.as_deref());
+    assert_eq!(Some(UNIX_EPOCH + Duration::from_secs(1445412480)), cookie.expires);
+    assert_eq!(Some(2592000), cookie.max_age);
+    assert!(cookie.secure);
+    assert!(cookie.http_only);
+    assert_eq!(Some("Lax"), cookie.same_site.as_deref());
+
+    // the obsolete date formats
+    let cookie = Cookie::parse("a=1; expires=Wednesday, 21-Oct-15 07:28:00 GMT").unwrap();
+    assert_eq!(Some(UNIX_EPOCH + Duration::from_secs(1445412480)), cookie.expires);
+
+    let cookie = Cookie::parse("a=1; expires=Wed Oct 21 07:28:00 2015").unwrap();
+    assert_eq!(Some(UNIX_EPOCH + Duration::from_secs(1445412480)), cookie.expires);
+
+    assert!(Cookie::parse("no-value").is_none());
+}
+
+#[test]
+fn test_cookies() {
+    let (base, server) = common::serve(1, |_, _| {
+        common::response(
+            "200 OK",
+            &[
+                ("Set-Cookie", "a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Path=/"),
+                ("Set-Cookie", "b=2; HttpOnly"),
+            ],
+            b"",
+        )
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    let cookies = response.cookies();
+
+    assert_eq!(2, cookies.len());
+    assert_eq!(("a", "1"), (cookies[0].name.as_str(), cookies[0].value.as_str()));
+    assert!(cookies[0].expires.is_some());
+    assert_eq!(("b", "2"), (cookies[1].name.as_str(), cookies[1].value.as_str()));
+    assert!(cookies[1].http_only);
+
+    server.join().unwrap();
+}
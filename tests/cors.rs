@@ -0,0 +1,45 @@
+extern crate easy_http_request;
+
+mod common;
+
+use easy_http_request::url::Url;
+use easy_http_request::{DefaultHttpRequest, HttpRequestMethod};
+
+#[test]
+fn test_preflight() {
+    let (base, server) = common::serve(1, |_, _| {
+        common::response(
+            "204 No Content",
+            &[
+                ("Access-Control-Allow-Origin", "https://example.com"),
+                ("Access-Control-Allow-Methods", "GET, PUT"),
+                ("Access-Control-Allow-Headers", "X-Token, Content-Type"),
+                ("Access-Control-Allow-Credentials", "true"),
+                ("Access-Control-Max-Age", "600"),
+            ],
+            b"",
+        )
+    });
+
+    let preflight = DefaultHttpRequest::preflight(
+        Url::parse(&base).unwrap(),
+        "https://example.com",
+        HttpRequestMethod::PUT,
+        &["X-Token", "Content-Type"],
+    )
+    .unwrap();
+
+    assert_eq!(204, preflight.status_code);
+    assert_eq!(Some("https://example.com"), preflight.allow_origin.as_deref());
+    assert_eq!(vec!["GET", "PUT"], preflight.allow_methods);
+    assert_eq!(vec!["X-Token", "Content-Type"], preflight.allow_headers);
+    assert!(preflight.allow_credentials);
+    assert_eq!(Some(600), preflight.max_age);
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("OPTIONS", requests[0].method);
+    assert_eq!(Some("https://example.com"), requests[0].header("Origin"));
+    assert_eq!(Some("PUT"), requests[0].header("Access-Control-Request-Method"));
+    assert_eq!(Some("X-Token, Content-Type"), requests[0].header("Access-Control-Request-Headers"));
+}
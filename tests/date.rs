@@ -0,0 +1,77 @@
+extern crate easy_http_request;
+
+mod common;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use easy_http_request::{format_http_date, parse_http_date, DefaultHttpRequest};
+
+#[test]
+fn test_format_http_date() {
+    assert_eq!("Thu, 01 Jan 1970 00:00:00 GMT", format_http_date(UNIX_EPOCH));
+    assert_eq!(
+        "Sun, 06 Nov 1994 08:49:37 GMT",
+        format_http_date(UNIX_EPOCH + Duration::from_secs(784111777))
+    );
+    assert_eq!(
+        "Tue, 29 Feb 2000 12:00:00 GMT",
+        format_http_date(UNIX_EPOCH + Duration::from_secs(951825600))
+    );
+
+    for secs in [0, 784111777, 951825600, 1445412480, 4102444799] {
+        let time = UNIX_EPOCH + Duration::from_secs(secs);
+
+        assert_eq!(Some(time), parse_http_date(&format_http_date(time)));
+    }
+}
+
+#[test]
+fn test_parse_http_date_invalid() {
+    assert_eq!(
+        Some(UNIX_EPOCH + Duration::from_secs(951782400)),
+        parse_http_date("Tue, 29 Feb 2000 00:00:00 GMT")
+    );
+
+    for s in [
+        "Thu, 31 Feb 2000 00:00:00 GMT",
+        "Mon, 29 Feb 2100 00:00:00 GMT",
+        "Fri, 29 Feb 2019 00:00:00 GMT",
+        "Sat, 31 Apr 2000 00:00:00 GMT",
+        "Sun, 06 Nov 500000000000 08:49:37 GMT",
+        "Sun, 06 Nov 18446744073709551615 08:49:37 GMT",
+        "Sun Nov  6 08:49:37 10000",
+        "Sunday, 06-Nov-500000000000 08:49:37 GMT",
+    ] {
+        assert_eq!(None, parse_http_date(s), "{}", s);
+    }
+}
+
+#[test]
+fn test_date() {
+    let (base, server) = common::serve(1, |_, _| {
+        common::response("200 OK", &[("Date", "Sun Nov  6 08:49:37 1994")], b"")
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert_eq!(Some(UNIX_EPOCH + Duration::from_secs(784111777)), response.date());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_invalid_date() {
+    let dates = ["Sun, 06 Nov 500000000000 08:49:37 GMT", "Thu, 31 Feb 2000 00:00:00 GMT"];
+
+    let (base, server) = common::serve(dates.len(), move |i, _| {
+        common::response("200 OK", &[("Date", dates[i])], b"")
+    });
+
+    for _ in dates.iter() {
+        let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+        assert_eq!(None, response.date());
+    }
+
+    server.join().unwrap();
+}
@@ -0,0 +1,103 @@
+extern crate easy_http_request;
+extern crate flate2;
+
+mod common;
+
+use std::io::Write;
+
+use easy_http_request::DefaultHttpRequest;
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+
+#[test]
+fn test_chunked_gzip() {
+    let text = "Hello, easy-http-request! ".repeat(100);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let (base, server) = common::serve(1, move |_, _| {
+        let mut response = b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n".to_vec();
+
+        for chunk in compressed.chunks(16) {
+            response.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+            response.extend_from_slice(chunk);
+            response.extend_from_slice(b"\r\n");
+        }
+
+        response.extend_from_slice(b"0\r\n\r\n");
+
+        response
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert_eq!(text.as_bytes(), response.body.as_slice());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_sniff_compression() {
+    let text = "Hello, easy-http-request! ".repeat(100);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes()).unwrap();
+    let gzipped = encoder.finish().unwrap();
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes()).unwrap();
+    let zlibbed = encoder.finish().unwrap();
+
+    let bodies = [gzipped.clone(), zlibbed, text.clone().into_bytes(), gzipped.clone()];
+
+    let (base, server) =
+        common::serve(bodies.len(), move |i, _| common::response("200 OK", &[], &bodies[i]));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.sniff_compression = true;
+
+    for _ in 0..3 {
+        let response = request.send_preserved().unwrap();
+
+        assert_eq!(text.as_bytes(), response.body.as_slice());
+    }
+
+    // disabled by default
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert_eq!(gzipped, response.body);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_empty_compressed_body() {
+    let (base, server) = common::serve(4, |i, _| {
+        match i {
+            // the headers of the body which a GET request would get
+            0 => b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: 20\r\nConnection: close\r\n\r\n"
+                .to_vec(),
+            1 => b"HTTP/1.1 304 Not Modified\r\nContent-Encoding: gzip\r\nConnection: close\r\n\r\n".to_vec(),
+            2 => b"HTTP/1.1 204 No Content\r\nContent-Encoding: deflate\r\nConnection: close\r\n\r\n".to_vec(),
+            _ => common::response("200 OK", &[("Content-Encoding", "gzip")], b""),
+        }
+    });
+
+    let response = DefaultHttpRequest::head_from_url_str(&base).unwrap().send().unwrap();
+
+    assert_eq!(200, response.status_code);
+    assert!(response.body.is_empty());
+
+    for status_code in [304, 204, 200] {
+        let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+        assert_eq!(status_code, response.status_code);
+        assert!(response.body.is_empty());
+    }
+
+    server.join().unwrap();
+}
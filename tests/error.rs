@@ -0,0 +1,74 @@
+extern crate easy_http_request;
+
+mod common;
+
+use std::error::Error;
+use std::sync::mpsc;
+use std::thread;
+
+use easy_http_request::{DefaultHttpRequest, HttpRequestError, HttpResponse, HttpResponseReader};
+
+fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+#[test]
+fn test_send_sync() {
+    assert_send_sync::<HttpRequestError>();
+    assert_send_sync::<HttpResponse>();
+
+    fn assert_send<T: Send + 'static>() {}
+
+    assert_send::<HttpResponseReader>();
+}
+
+#[test]
+fn test_error_through_channel() {
+    let (sender, receiver) = mpsc::channel::<Result<HttpResponse, Box<dyn Error + Send + Sync>>>();
+
+    thread::spawn(move || {
+        sender.send(Err(HttpRequestError::LocalNotAllow.into())).unwrap();
+    });
+
+    let err = receiver.recv().unwrap().unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<HttpRequestError>(),
+        Some(HttpRequestError::LocalNotAllow)
+    ));
+}
+
+#[test]
+fn test_send_checked() {
+    let (base, server) = common::serve(2, |i, _| {
+        match i {
+            0 => common::response("200 OK", &[], b""),
+            _ => common::response("503 Service Unavailable", &[], b"busy"),
+        }
+    });
+
+    assert_eq!(
+        200,
+        DefaultHttpRequest::get_from_url_str(&base).unwrap().send_checked().unwrap().status_code
+    );
+
+    match DefaultHttpRequest::get_from_url_str(&base).unwrap().send_checked() {
+        Err(HttpRequestError::StatusError(response)) => {
+            assert_eq!(503, response.status_code);
+            assert_eq!(b"busy".to_vec(), response.body);
+        }
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_unsupported_scheme() {
+    for url in ["file:///etc/passwd", "ftp://127.0.0.1/"] {
+        match DefaultHttpRequest::get_from_url_str(url).unwrap().send() {
+            Err(HttpRequestError::Other(text)) => {
+                assert_eq!("Only http and https schemes are supported.", text)
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+}
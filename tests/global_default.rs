@@ -0,0 +1,23 @@
+extern crate easy_http_request;
+
+// the global default affects every request of the process, so this test has a binary of its own
+
+use easy_http_request::{DefaultHttpRequest, HttpRequestOptions};
+
+#[test]
+fn test_global_default() {
+    assert!(
+        DefaultHttpRequest::get_from_url_str("http://example.test").unwrap().options.allow_local
+    );
+
+    assert!(HttpRequestOptions::set_global_default(
+        HttpRequestOptions::builder().allow_local(false).max_connection_time(5000).build(),
+    ));
+
+    let request = DefaultHttpRequest::get_from_url_str("http://example.test").unwrap();
+
+    assert!(!request.options.allow_local);
+    assert_eq!(5000, request.options.max_connection_time);
+
+    assert!(!HttpRequestOptions::set_global_default(HttpRequestOptions::default()));
+}
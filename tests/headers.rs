@@ -0,0 +1,384 @@
+extern crate easy_http_request;
+
+mod common;
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use easy_http_request::{mime, DefaultHttpRequest, HttpRequestBody, HttpRequestError};
+
+#[test]
+fn test_preserve_raw_headers() {
+    let (base, server) = common::serve(2, |_, _| {
+        common::response(
+            "200 OK",
+            &[("X-Custom-Header", "1"), ("set-cookie", "a=1"), ("Set-Cookie", "b=2")],
+            b"",
+        )
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    assert!(request.send_preserved().unwrap().raw_headers.is_none());
+
+    request.options.preserve_raw_headers = true;
+
+    let response = request.send().unwrap();
+
+    let raw_headers = response.raw_headers.unwrap();
+
+    let names: Vec<&str> = raw_headers.iter().map(|(k, _)| k.as_str()).collect();
+
+    assert_eq!(&["X-Custom-Header", "set-cookie", "set-cookie"], &names[..3]);
+    assert_eq!("a=1", raw_headers[1].1);
+    assert_eq!("b=2", raw_headers[2].1);
+    assert_eq!(Some("a=1, b=2"), response.headers.get("set-cookie").map(|v| v.as_str()));
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_display() {
+    let body = "a".repeat(1030);
+
+    let (base, server) = common::serve(1, move |_, _| {
+        common::response("404 Not Found", &[("X-B", "2"), ("X-A", "1")], body.as_bytes())
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    let s = response.to_string();
+
+    assert!(
+        s.starts_with("404 Not Found\nconnection: close\ncontent-length: 1030\nx-a: 1\nx-b: 2\n\n")
+    );
+    assert!(s.ends_with(&format!("{}\n... (6 more bytes)", "a".repeat(1024))));
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_on_unauthorized() {
+    let (base, server) = common::serve(2, |_, request| {
+        if request.header("Authorization") == Some("Bearer fresh") {
+            common::response("200 OK", &[], b"")
+        } else {
+            common::response("401 Unauthorized", &[], b"")
+        }
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.set_header("authorization".to_string(), "Bearer expired".to_string());
+
+    let mut count = 0;
+
+    let response = request
+        .on_unauthorized(move || {
+            count += 1;
+
+            assert_eq!(1, count);
+
+            "Bearer fresh".to_string()
+        })
+        .send()
+        .unwrap();
+
+    assert_eq!(200, response.status_code);
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("Bearer expired"), requests[0].header("Authorization"));
+    assert_eq!(Some("Bearer fresh"), requests[1].header("Authorization"));
+}
+
+#[test]
+fn test_trace_writer() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let trace = Arc::new(Mutex::new(Vec::new()));
+
+    let mut request = DefaultHttpRequest::post_from_url_str(format!("{}/path", base)).unwrap();
+
+    request.set_header("X-Test".to_string(), "yes".to_string());
+    request.body = Some(HttpRequestBody::json_str("{}"));
+    request.options.trace_writer = Some(trace.clone());
+
+    request.send().unwrap();
+
+    server.join().unwrap();
+
+    let trace = String::from_utf8(trace.lock().unwrap().clone()).unwrap();
+
+    assert!(trace.starts_with(&format!("> POST {}/path\n", base)));
+    assert!(trace.contains("> X-Test: yes\n"));
+    assert!(trace.ends_with("> [2 bytes of application/json]\n"));
+}
+
+#[test]
+fn test_extra_headers() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.set_header("Accept".to_string(), "text/html".to_string());
+    request.append_header("Accept".to_string(), "application/json".to_string());
+    request.append_header("X-Forwarded-For".to_string(), "10.0.0.1".to_string());
+    request.append_header("X-Forwarded-For".to_string(), "10.0.0.2".to_string());
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    let values = |name: &str| -> Vec<&str> {
+        requests[0]
+            .headers
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+            .collect()
+    };
+
+    assert_eq!(vec!["text/html", "application/json"], values("Accept"));
+    assert_eq!(vec!["10.0.0.1", "10.0.0.2"], values("X-Forwarded-For"));
+}
+
+#[test]
+fn test_content_type_mime() {
+    let (base, server) = common::serve(2, |i, _| {
+        match i {
+            0 => common::response("200 OK", &[("Content-Type", "text/html; charset=UTF-8")], b""),
+            _ => common::response("200 OK", &[("Content-Type", "not a mime")], b""),
+        }
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    let content_type = response.content_type_mime().unwrap();
+
+    assert_eq!(mime::TEXT_HTML, content_type.essence_str());
+    assert_eq!(Some(mime::UTF_8), content_type.get_param(mime::CHARSET));
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert_eq!(None, response.content_type_mime());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_expect_content_type() {
+    let (base, server) = common::serve(1, |_, _| {
+        common::response("200 OK", &[("Content-Type", "text/html; charset=UTF-8")], b"")
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert!(response.expect_content_type(&mime::TEXT_HTML_UTF_8).is_ok());
+    assert!(response.expect_content_type(&mime::TEXT_HTML).is_ok());
+
+    match response.expect_content_type(&mime::APPLICATION_JSON) {
+        Err(HttpRequestError::UnexpectedContentType {
+            expected,
+            found,
+        }) => {
+            assert_eq!(mime::APPLICATION_JSON, expected);
+            assert_eq!(Some("text/html; charset=UTF-8".to_string()), found);
+        }
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_skip_interim_responses() {
+    let (base, server) = common::serve(3, |_, _| {
+        let mut response = b"HTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload\r\n\r\n\
+                             HTTP/1.1 102 Processing\r\n\r\n"
+            .to_vec();
+
+        response.extend_from_slice(&common::response("200 OK", &[("X-Final", "1")], b"final"));
+
+        response
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    let response = request.send_preserved().unwrap();
+
+    assert_eq!(200, response.status_code);
+    assert_eq!(Some("1"), response.header("X-Final"));
+    assert_eq!(None, response.header("Link"));
+    assert_eq!(b"final".to_vec(), response.body);
+
+    request.options.connection_pool = Some(Default::default());
+
+    assert_eq!(200, request.send_preserved().unwrap().status_code);
+
+    request.options.skip_interim_responses = false;
+
+    assert_eq!(103, request.send().unwrap().status_code);
+
+    server.join().unwrap();
+}
+
+/// Start a server which answers one request with `head` and then `chunk` every `interval` until the client goes away.
+fn serve_endless(
+    head: &'static [u8],
+    chunk: &'static [u8],
+    interval: Duration,
+) -> (String, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        common::read_request(&mut stream);
+
+        if stream.write_all(head).is_err() {
+            return;
+        }
+
+        while stream.write_all(chunk).and_then(|_| stream.flush()).is_ok() {
+            thread::sleep(interval);
+        }
+    });
+
+    (base, server)
+}
+
+/// A request sent through hyper, or one written by hand (a `GET` request with a body).
+fn interim_request(base: &str, by_hand: bool) -> DefaultHttpRequest {
+    let mut request = DefaultHttpRequest::get_from_url_str(base).unwrap();
+
+    if by_hand {
+        request.body = Some(HttpRequestBody::Raw(b"a".to_vec()));
+    }
+
+    request
+}
+
+#[test]
+fn test_interim_response_headers_too_large() {
+    for &by_hand in [false, true].iter() {
+        // the headers of the interim response never end
+        let (base, server) = serve_endless(
+            b"HTTP/1.1 103 Early Hints\r\n",
+            b"Link: </style.css>; rel=preload\r\n",
+            Duration::from_millis(0),
+        );
+
+        let mut request = interim_request(&base, by_hand);
+
+        request.options.max_response_headers_size = 16 * 1024;
+
+        let start_time = Instant::now();
+
+        match request.send() {
+            Err(HttpRequestError::HeadersTooLarge) if !by_hand => (),
+            // hyper limits the size of a response head it parses
+            Err(HttpRequestError::HyperError(_)) if by_hand => (),
+            other => panic!("{:?}", other),
+        }
+
+        assert!(start_time.elapsed() < Duration::from_secs(5));
+
+        server.join().unwrap();
+    }
+}
+
+#[test]
+fn test_too_many_interim_responses() {
+    for &by_hand in [false, true].iter() {
+        let (base, server) =
+            serve_endless(b"", b"HTTP/1.1 102 Processing\r\n\r\n", Duration::from_millis(0));
+
+        let request = interim_request(&base, by_hand);
+
+        match request.send() {
+            Err(HttpRequestError::Other(_)) => (),
+            other => panic!("{:?}", other),
+        }
+
+        server.join().unwrap();
+    }
+}
+
+#[test]
+fn test_interim_responses_timeout() {
+    for &by_hand in [false, true].iter() {
+        // every read is in time, but the responses never end before the limit of the connection time
+        let (base, server) =
+            serve_endless(b"", b"HTTP/1.1 102 Processing\r\n\r\n", Duration::from_millis(100));
+
+        let mut request = interim_request(&base, by_hand);
+
+        request.options.max_connection_time = 500;
+
+        match request.send() {
+            Err(HttpRequestError::TimeOut(elapsed)) => assert!(elapsed < Duration::from_secs(2)),
+            other => panic!("{:?}", other),
+        }
+
+        server.join().unwrap();
+    }
+}
+
+#[test]
+fn test_suggested_filename() {
+    let values = [
+        ("attachment; filename=\"report.pdf\"", Some("report.pdf")),
+        ("attachment; filename=plain.txt", Some("plain.txt")),
+        ("attachment; filename=\"a;b \\\"c\\\".txt\"", Some("a;b \"c\".txt")),
+        (
+            "attachment; filename=\"fallback.txt\"; filename*=UTF-8''%E5%A0%B1%E5%91%8A%20v2.txt",
+            Some("報告 v2.txt"),
+        ),
+        ("attachment; filename*=iso-8859-1'en'%A3%20rates.txt", Some("£ rates.txt")),
+        ("attachment; filename=\"../../etc/passwd\"", Some("passwd")),
+        ("attachment; filename=\"..\"", None),
+        ("inline", None),
+    ];
+
+    let (base, server) = common::serve(values.len(), move |i, _| {
+        common::response("200 OK", &[("Content-Disposition", values[i].0)], b"")
+    });
+
+    for (_, expected) in values.iter() {
+        let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+        assert_eq!(expected.map(String::from), response.suggested_filename());
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_case_preserved_headers() {
+    let (base, server) = common::serve(1, |_, _| {
+        common::response(
+            "200 OK",
+            &[("SOAPAction", "\"urn:Update\""), ("X-Other", "1"), ("Location", "/next")],
+            b"",
+        )
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.case_preserved_headers = vec!["soapaction".to_string(), "Location".to_string()];
+
+    let response = request.send().unwrap();
+
+    assert_eq!(Some("\"urn:Update\""), response.headers.get("SOAPAction").map(|v| v.as_str()));
+    assert!(!response.headers.contains_key("soapaction"));
+    assert_eq!(Some("1"), response.headers.get("x-other").map(|v| v.as_str()));
+    assert_eq!(Some("\"urn:Update\""), response.header("soapaction"));
+    assert_eq!(Some("/next"), response.header("location"));
+
+    server.join().unwrap();
+}
@@ -0,0 +1,110 @@
+// the test CA is trusted through `SSL_CERT_FILE`, which only OpenSSL reads
+#![cfg(all(unix, not(target_os = "macos")))]
+
+extern crate easy_http_request;
+
+mod common;
+
+use std::io::ErrorKind;
+use std::net::TcpListener;
+
+use easy_http_request::{DefaultHttpRequest, HttpRequestError};
+
+#[test]
+fn test_https_downgrade() {
+    common::trust_test_ca();
+
+    let (plain_base, plain_server) =
+        common::serve(1, |_, _| common::response("200 OK", &[], b"plain"));
+
+    let location = format!("{}/get", plain_base);
+
+    let (base, server) = common::serve_tls(3, move |_, _| {
+        common::response("302 Found", &[("Location", location.as_str())], b"")
+    });
+
+    match DefaultHttpRequest::get_from_url_str(&base).unwrap().send() {
+        Err(HttpRequestError::RedirectError {
+            url,
+            status,
+            ..
+        }) => {
+            assert_eq!(Some(format!("{}/get", plain_base)), url);
+            assert_eq!(Some(302), status);
+        }
+        other => panic!("{:?}", other),
+    }
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.allow_https_downgrade = true;
+
+    let response = request.send().unwrap();
+
+    assert_eq!(200, response.status_code);
+    assert_eq!(b"plain", response.body.as_slice());
+    assert!(response.was_redirected);
+
+    // also when the redirection is followed by hand
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_redirect_count = 0;
+
+    let options = request.options.clone();
+
+    let response = request.send().unwrap();
+
+    assert!(matches!(
+        response.follow(&options),
+        Err(HttpRequestError::RedirectError {
+            status: Some(302),
+            ..
+        })
+    ));
+
+    server.join().unwrap();
+    plain_server.join().unwrap();
+}
+
+#[test]
+fn test_https_only() {
+    common::trust_test_ca();
+
+    // a non-HTTPS URL is rejected before connecting
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    listener.set_nonblocking(true).unwrap();
+
+    let mut request =
+        DefaultHttpRequest::get_from_url_str(format!("http://{}/", listener.local_addr().unwrap()))
+            .unwrap();
+
+    request.options.https_only = true;
+
+    match request.send() {
+        Err(HttpRequestError::Other(_)) => (),
+        other => panic!("{:?}", other),
+    }
+
+    assert_eq!(ErrorKind::WouldBlock, listener.accept().unwrap_err().kind());
+
+    // a redirection to a non-HTTPS URL is not followed
+    let location = format!("http://{}/get", listener.local_addr().unwrap());
+
+    let (base, server) = common::serve_tls(1, move |_, _| {
+        common::response("302 Found", &[("Location", location.as_str())], b"")
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.https_only = true;
+
+    let response = request.send().unwrap();
+
+    assert_eq!(302, response.status_code);
+    assert!(!response.was_redirected);
+
+    assert_eq!(ErrorKind::WouldBlock, listener.accept().unwrap_err().kind());
+
+    server.join().unwrap();
+}
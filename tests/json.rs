@@ -0,0 +1,81 @@
+#![cfg(feature = "json")]
+
+extern crate easy_http_request;
+
+mod common;
+
+use easy_http_request::serde_json::{self, json};
+use easy_http_request::url::Url;
+use easy_http_request::{DefaultHttpRequest, HttpRequestError};
+
+#[test]
+fn test_post_json() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let value = json!({ "name": "magiclen", "tags": ["rust", "http"] });
+
+    DefaultHttpRequest::post_json(Url::parse(&base).unwrap(), &value).unwrap().send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("application/json"), requests[0].header("Content-Type"));
+    assert_eq!(value, serde_json::from_slice::<serde_json::Value>(&requests[0].body).unwrap());
+}
+
+#[test]
+fn test_delete_json() {
+    let (base, server) = common::serve(1, |_, _| common::response("204 No Content", &[], b""));
+
+    let value = json!({ "ids": [1, 2, 3] });
+
+    DefaultHttpRequest::delete_json(Url::parse(&base).unwrap(), &value).unwrap().send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("DELETE", requests[0].method);
+    assert_eq!(Some("application/json"), requests[0].header("Content-Type"));
+    assert_eq!(value, serde_json::from_slice::<serde_json::Value>(&requests[0].body).unwrap());
+}
+
+#[test]
+fn test_response_json() {
+    let (base, server) =
+        common::serve(1, |_, _| common::response("200 OK", &[], br#"{"id":1,"tags":["a"]}"#));
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    let value: serde_json::Value = response.json().unwrap();
+
+    assert_eq!(json!({ "id": 1, "tags": ["a"] }), value);
+
+    assert!(matches!(response.json::<Vec<u8>>(), Err(HttpRequestError::JsonError(_))));
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_empty_body_json() {
+    let (base, server) = common::serve(2, |i, _| {
+        if i == 0 {
+            common::response("204 No Content", &[], b"")
+        } else {
+            common::response("200 OK", &[], b"[1]")
+        }
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert!(matches!(
+        response.json::<serde_json::Value>(),
+        Err(HttpRequestError::EmptyBody {
+            status: 204
+        })
+    ));
+    assert!(response.json_opt::<serde_json::Value>().unwrap().is_none());
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert_eq!(Some(vec![1]), response.json_opt::<Vec<u8>>().unwrap());
+
+    server.join().unwrap();
+}
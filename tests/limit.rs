@@ -0,0 +1,188 @@
+extern crate easy_http_request;
+
+mod common;
+
+use easy_http_request::{DefaultHttpRequest, HttpRequestError};
+
+#[test]
+fn test_max_response_headers_size() {
+    let (base, server) = common::serve(2, |_, _| {
+        common::response("200 OK", &[("X-Padding", &"a".repeat(1024))], b"")
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.send_preserved().unwrap();
+
+    request.options.max_response_headers_size = 1024;
+
+    match request.send() {
+        Err(HttpRequestError::HeadersTooLarge) => (),
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_max_header_count() {
+    let (base, server) = common::serve(2, |_, _| {
+        common::response(
+            "200 OK",
+            &[("Set-Cookie", "a=1"), ("Set-Cookie", "b=2"), ("X-A", "1")],
+            b"",
+        )
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_header_count = 5;
+
+    request.send_preserved().unwrap();
+
+    // the repeated `Set-Cookie` headers count twice
+    request.options.max_header_count = 4;
+
+    match request.send() {
+        Err(HttpRequestError::TooManyHeaders {
+            limit: 4,
+        }) => (),
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_max_response_body_size() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], &[b'a'; 4096]));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_response_body_size = Some(1024);
+
+    match request.send() {
+        Err(HttpRequestError::TooLarge {
+            size,
+            limit,
+        }) => {
+            assert!(size > 1024);
+            assert_eq!(1024, limit);
+        }
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_redirection_body_not_read() {
+    let (base, server) = common::serve(3, |i, _| {
+        match i {
+            0 | 1 => {
+                common::response(
+                    "302 Found",
+                    &[("Location", &format!("/{}", i + 1))],
+                    &[b'a'; 4096],
+                )
+            }
+            _ => common::response("200 OK", &[], b"final"),
+        }
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_response_body_size = Some(1024);
+
+    assert_eq!(b"final".to_vec(), request.send().unwrap().body);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_max_error_body_size() {
+    let (base, server) = common::serve(2, |i, _| {
+        let status = if i == 0 {
+            "200 OK"
+        } else {
+            "500 Internal Server Error"
+        };
+
+        common::response(status, &[], &[b'a'; 2048])
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_error_body_size = Some(1024);
+
+    assert_eq!(2048, request.send_preserved().unwrap().body.len());
+
+    match request.send() {
+        Err(HttpRequestError::TooLarge {
+            limit,
+            ..
+        }) => assert_eq!(1024, limit),
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_unlimited_response_body_size() {
+    let (base, server) =
+        common::serve(1, |_, _| common::response("200 OK", &[], &vec![b'a'; 2 * 1024 * 1024]));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_response_body_size = None;
+
+    assert_eq!(2 * 1024 * 1024, request.send().unwrap().body.len());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_return_partial_on_error() {
+    // the connection is closed before the advertised length has been sent
+    let (base, server) = common::serve(2, |_, _| {
+        b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\npartial".to_vec()
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    assert!(request.send_preserved().is_err());
+
+    request.options.return_partial_on_error = true;
+
+    let response = request.send().unwrap();
+
+    assert!(response.truncated);
+    assert_eq!(b"partial", response.body.as_slice());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_validate_content_length() {
+    let (base, server) = common::serve(1, |_, _| {
+        b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\npartial".to_vec()
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.validate_content_length = true;
+
+    match request.send() {
+        Err(HttpRequestError::IncompleteBody {
+            expected,
+            received,
+        }) => {
+            assert_eq!(100, expected);
+            assert_eq!(7, received);
+        }
+        result => panic!("{:?}", result),
+    }
+
+    server.join().unwrap();
+}
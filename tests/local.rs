@@ -0,0 +1,41 @@
+extern crate easy_http_request;
+
+use easy_http_request::url::{Host, Url};
+use easy_http_request::{is_local_host, is_local_url, DefaultHttpRequest, HttpRequestError};
+
+#[test]
+fn test_get_safe() {
+    for url in &["http://127.0.0.1/", "http://localhost:8080/", "http://[::1]/", "http://10.0.0.1/"]
+    {
+        match DefaultHttpRequest::get_safe_from_url_str(url).unwrap().send() {
+            Err(HttpRequestError::LocalNotAllow) => (),
+            other => panic!("{}: {:?}", url, other),
+        }
+    }
+}
+
+#[test]
+fn test_is_local() {
+    for url in
+        &["http://127.0.0.1/", "http://localhost:8080/", "http://[fe80::1]/", "http://192.168.1.1/"]
+    {
+        assert!(is_local_url(&Url::parse(url).unwrap()), "{}", url);
+    }
+
+    for url in &["http://8.8.8.8/", "http://magiclen.org/", "http://[2001:4860::1]/", "file:///tmp"]
+    {
+        assert!(!is_local_url(&Url::parse(url).unwrap()), "{}", url);
+    }
+
+    assert!(is_local_host(&Host::parse("10.1.2.3").unwrap()));
+    assert!(!is_local_host(&Host::Domain("example.test")));
+}
+
+#[test]
+fn test_ipv6_zone_id() {
+    // the URL standard has no zone identifiers, so such a URL is rejected before any host check
+    match DefaultHttpRequest::get_from_url_str("http://[fe80::1%25eth0]/") {
+        Err(HttpRequestError::UrlParseError(_)) => (),
+        other => panic!("{:?}", other),
+    }
+}
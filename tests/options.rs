@@ -0,0 +1,42 @@
+extern crate easy_http_request;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+
+use easy_http_request::HttpRequestOptions;
+
+#[test]
+fn test_builder() {
+    let options = HttpRequestOptions::builder()
+        .max_redirect_count(1)
+        .allow_local(false)
+        .timeout(Duration::from_secs(3))
+        .resolve("backend.test", IpAddr::V4(Ipv4Addr::LOCALHOST))
+        .build();
+
+    assert_eq!(1, options.max_redirect_count);
+    assert!(!options.allow_local);
+    assert_eq!(3000, options.max_connection_time);
+    assert_eq!(
+        vec![("backend.test".to_string(), IpAddr::V4(Ipv4Addr::LOCALHOST))],
+        options.resolve
+    );
+
+    // the other options keep their default values
+    let default = HttpRequestOptions::default();
+
+    assert_eq!(default.max_response_body_size, options.max_response_body_size);
+    assert_eq!(default.allow_https_downgrade, options.allow_https_downgrade);
+}
+
+#[test]
+fn test_builder_timeout() {
+    let timeout =
+        |timeout| HttpRequestOptions::builder().timeout(timeout).build().max_connection_time;
+
+    // `0` means unlimited, so a short duration must not become it
+    assert_eq!(0, timeout(Duration::ZERO));
+    assert_eq!(1, timeout(Duration::from_micros(500)));
+    assert_eq!(1001, timeout(Duration::from_micros(1_000_001)));
+    assert_eq!(u64::MAX, timeout(Duration::MAX));
+}
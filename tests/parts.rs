@@ -0,0 +1,216 @@
+extern crate easy_http_request;
+
+use std::collections::HashMap;
+
+use easy_http_request::{
+    mime, DefaultHttpRequest, HttpRequestBody, HttpRequestMethod, StaticHttpRequest,
+    DEFAULT_USER_AGENT,
+};
+
+#[test]
+fn test_build_request_parts() {
+    let mut request = DefaultHttpRequest::post_from_url_str("https://magiclen.org/path").unwrap();
+
+    let mut query = HashMap::new();
+    query.insert("a".to_string(), "1".to_string());
+    request.query = Some(query);
+
+    let mut headers = HashMap::new();
+    headers.insert("X-Test".to_string(), "yes".to_string());
+    request.headers = Some(headers);
+
+    request.body = Some(HttpRequestBody::Text {
+        content_type: mime::TEXT_PLAIN,
+        body: "hello".to_string(),
+    });
+
+    let parts = request.build_request_parts().unwrap();
+
+    assert_eq!(HttpRequestMethod::POST, parts.method);
+    assert_eq!("https://magiclen.org/path?a=1", parts.url.as_str());
+    assert_eq!(Some(&[b"yes".to_vec()][..]), parts.headers.get_raw("X-Test"));
+    assert_eq!(
+        Some(&[DEFAULT_USER_AGENT.as_bytes().to_vec()][..]),
+        parts.headers.get_raw("User-Agent")
+    );
+    assert_eq!(Some(&[b"text/plain".to_vec()][..]), parts.headers.get_raw("Content-Type"));
+    assert_eq!(Some(&[b"5".to_vec()][..]), parts.headers.get_raw("Content-Length"));
+    assert_eq!(Some(&b"hello"[..]), parts.body.as_deref());
+}
+
+#[test]
+fn test_json_str() {
+    let mut request = DefaultHttpRequest::post_from_url_str("https://magiclen.org/").unwrap();
+
+    request.body = Some(HttpRequestBody::json_str(r#"{"a":1}"#));
+
+    let parts = request.build_request_parts().unwrap();
+
+    assert_eq!(Some(&[b"application/json".to_vec()][..]), parts.headers.get_raw("Content-Type"));
+    assert_eq!(Some(&br#"{"a":1}"#[..]), parts.body.as_deref());
+}
+
+#[test]
+fn test_set_header() {
+    let mut request = StaticHttpRequest::get_from_url_str("https://magiclen.org/").unwrap();
+
+    request.set_header("X-Test", "1");
+    request.set_header("x-test", "2");
+    request.set_header("Accept", "text/plain");
+
+    let parts = request.build_request_parts().unwrap();
+
+    assert_eq!(Some(&[b"2".to_vec()][..]), parts.headers.get_raw("X-Test"));
+
+    request.remove_header("ACCEPT");
+
+    assert_eq!(1, request.headers.as_ref().unwrap().len());
+
+    request.clear_headers();
+
+    assert!(request.headers.is_none());
+}
+
+#[test]
+fn test_add_query() {
+    let mut request = StaticHttpRequest::get_from_url_str("https://magiclen.org/").unwrap();
+
+    request.add_query("a", "1");
+    request.add_query("a", "2");
+
+    let parts = request.build_request_parts().unwrap();
+
+    assert_eq!("https://magiclen.org/?a=2", parts.url.as_str());
+
+    // spaces are encoded as `+`
+    request.add_query("a", "x y+z");
+
+    let parts = request.build_request_parts().unwrap();
+
+    assert_eq!("https://magiclen.org/?a=x+y%2Bz", parts.url.as_str());
+}
+
+#[test]
+fn test_push_path_segment() {
+    let mut request = StaticHttpRequest::get_from_url_str("https://magiclen.org/api/").unwrap();
+
+    request.push_path_segment("users").unwrap();
+    request.push_path_segment("a/b?c#d").unwrap();
+    request.push_path_segment("posts").unwrap();
+
+    assert_eq!("https://magiclen.org/api/users/a%2Fb%3Fc%23d/posts", request.url.as_str());
+}
+
+#[test]
+fn test_send_default_user_agent() {
+    let mut request = StaticHttpRequest::get_from_url_str("https://magiclen.org/").unwrap();
+
+    request.options.send_default_user_agent = false;
+
+    assert!(request.build_request_parts().unwrap().headers.get_raw("User-Agent").is_none());
+
+    request.set_header("User-Agent", "test");
+
+    assert_eq!(
+        Some(&[b"test".to_vec()][..]),
+        request.build_request_parts().unwrap().headers.get_raw("User-Agent")
+    );
+}
+
+#[test]
+fn test_idempotency_key() {
+    let mut request = DefaultHttpRequest::post_from_url_str("https://magiclen.org/").unwrap();
+
+    request.set_header("idempotency-key".to_string(), "old".to_string());
+
+    let request = request.idempotency_key("8e03978e-40d5-43e8-bc93-6894a57f9324");
+
+    let parts = request.build_request_parts().unwrap();
+
+    assert_eq!(
+        Some(&[b"8e03978e-40d5-43e8-bc93-6894a57f9324".to_vec()][..]),
+        parts.headers.get_raw("Idempotency-Key")
+    );
+
+    let request = StaticHttpRequest::post_from_url_str("https://magiclen.org/")
+        .unwrap()
+        .idempotency_key("1f0b6c1e");
+
+    assert_eq!(
+        Some(&[b"1f0b6c1e".to_vec()][..]),
+        request.build_request_parts().unwrap().headers.get_raw("Idempotency-Key")
+    );
+}
+
+#[test]
+fn test_to_curl() {
+    let mut request =
+        StaticHttpRequest::post_from_url_str("https://magiclen.org/path?a=1").unwrap();
+
+    request.set_header("User-Agent", "test");
+    request.set_header("X-Quote", "it's");
+    request.body = Some(HttpRequestBody::Text {
+        content_type: mime::TEXT_PLAIN,
+        body: "a 'b'".to_string(),
+    });
+
+    let command = request.to_curl().unwrap();
+
+    assert!(command.starts_with("curl -X POST "));
+    assert!(command.contains(" -H 'User-Agent: test'"));
+    assert!(command.contains(" -H 'X-Quote: it'\\''s'"));
+    assert!(command.contains(" -H 'Content-Type: text/plain'"));
+    assert!(!command.contains("Content-Length"));
+    assert!(command.ends_with(" --data-raw 'a '\\''b'\\''' 'https://magiclen.org/path?a=1'"));
+
+    request.body = Some(HttpRequestBody::Raw(vec![0, 255]));
+
+    let command = request.to_curl().unwrap();
+
+    assert!(command.starts_with("printf '\\000\\377' | curl -X POST "));
+    assert!(command.contains(" --data-binary @- "));
+
+    let request = StaticHttpRequest::head_from_url_str("https://magiclen.org/").unwrap();
+
+    assert!(request.to_curl().unwrap().starts_with("curl --head "));
+}
+
+#[test]
+fn test_from_parts() {
+    let request = DefaultHttpRequest::from_parts(
+        HttpRequestMethod::GET,
+        "https",
+        "magiclen.org",
+        Some(8443),
+        "/a b/c",
+        Some("d=1"),
+    )
+    .unwrap();
+
+    assert_eq!("https://magiclen.org:8443/a%20b/c?d=1", request.url.as_str());
+
+    let request =
+        DefaultHttpRequest::from_parts(HttpRequestMethod::GET, "http", "[::1]", None, "", None)
+            .unwrap();
+
+    assert_eq!("http://[::1]/", request.url.as_str());
+
+    assert!(DefaultHttpRequest::from_parts(
+        HttpRequestMethod::GET,
+        "ftp",
+        "magiclen.org",
+        None,
+        "/",
+        None
+    )
+    .is_err());
+    assert!(DefaultHttpRequest::from_parts(
+        HttpRequestMethod::GET,
+        "http",
+        "magiclen.org/evil",
+        None,
+        "/",
+        None
+    )
+    .is_err());
+}
@@ -1,6 +1,14 @@
 extern crate easy_http_request;
 
-use easy_http_request::DefaultHttpRequest;
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use easy_http_request::url::Url;
+use easy_http_request::{
+    DefaultHttpRequest, HttpRequestBody, HttpRequestError, HttpRequestMethod, HttpRequestOptions,
+    HttpResponseHead, RedirectAction, RedirectMethodPolicy,
+};
 
 #[test]
 fn test_redirect() {
@@ -11,3 +19,377 @@ fn test_redirect() {
 
     assert_eq!(200, response.status_code);
 }
+
+#[test]
+fn test_was_redirected() {
+    let (base, server) = common::serve(2, |i, _| {
+        if i == 0 {
+            common::response("302 Found", &[("Location", "/target")], b"")
+        } else {
+            common::response("200 OK", &[], b"target")
+        }
+    });
+
+    let response = DefaultHttpRequest::get_from_url_str(&base).unwrap().send().unwrap();
+
+    assert_eq!(200, response.status_code);
+    assert!(response.was_redirected);
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("/target", requests[1].target);
+}
+
+#[test]
+fn test_relative_location() {
+    let (base, server) = common::serve(4, |i, request| {
+        let location = match i {
+            0 => "sub/../next".to_string(),
+            1 => "?foo=bar".to_string(),
+            2 => {
+                // scheme-relative
+                let port = request.header("Host").unwrap().rsplit(':').next().unwrap();
+
+                format!("//localhost:{}/final", port)
+            }
+            _ => return common::response("200 OK", &[], b""),
+        };
+
+        common::response("302 Found", &[("Location", &location)], b"")
+    });
+
+    let response =
+        DefaultHttpRequest::get_from_url_str(format!("{}/dir/page", base)).unwrap().send().unwrap();
+
+    assert_eq!(200, response.status_code);
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("/dir/next", requests[1].target);
+    assert_eq!("/dir/next?foo=bar", requests[2].target);
+    assert_eq!("/final", requests[3].target);
+    assert!(requests[3].header("Host").unwrap().starts_with("localhost:"));
+}
+
+#[test]
+fn test_max_redirect_count() {
+    let (base, server) =
+        common::serve(3, |_, _| common::response("302 Found", &[("Location", "/loop")], b""));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_redirect_count = 2;
+
+    let response = request.send().unwrap();
+
+    assert_eq!(302, response.status_code);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_same_host_redirects_only() {
+    let (base, server) = common::serve(2, |i, _| {
+        if i == 0 {
+            common::response("302 Found", &[("Location", "/target")], b"")
+        } else {
+            common::response("200 OK", &[], b"target")
+        }
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.same_host_redirects_only = true;
+
+    let response = request.send_preserved().unwrap();
+
+    assert!(response.was_redirected);
+
+    server.join().unwrap();
+
+    let port = Url::parse(&base).unwrap().port().unwrap();
+
+    let location = format!("http://localhost:{}/target", port);
+
+    let (base, server) = common::serve(1, move |_, _| {
+        common::response("302 Found", &[("Location", &location)], b"")
+    });
+
+    request.url = Url::parse(&base).unwrap();
+
+    match request.send() {
+        Err(HttpRequestError::RedirectError {
+            url,
+            status,
+            ..
+        }) => {
+            assert_eq!(Some(format!("http://localhost:{}/target", port)), url);
+            assert_eq!(Some(302), status);
+        }
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_redirect_handler() {
+    let (base, server) = common::serve(2, |i, _| {
+        match i {
+            0 => common::response("302 Found", &[("Location", "/blocked")], b""),
+            _ => common::response("301 Moved Permanently", &[("Location", "/stop")], b""),
+        }
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(format!("{}/start", base)).unwrap();
+
+    request.options.redirect_handler =
+        Some(Arc::new(Mutex::new(|location: &Url, status_code: u16, head: &HttpResponseHead| {
+            match location.path() {
+                "/blocked" => {
+                    assert_eq!(302, status_code);
+                    assert_eq!(Some("/blocked"), head.header("Location"));
+
+                    RedirectAction::Follow(location.join("/rewritten").unwrap())
+                }
+                _ => RedirectAction::Stop,
+            }
+        })));
+
+    let response = request.send().unwrap();
+
+    assert_eq!(301, response.status_code);
+    assert!(response.was_redirected);
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("/rewritten", requests[1].target);
+}
+
+#[test]
+fn test_redirect_handler_error() {
+    let (base, server) =
+        common::serve(1, |_, _| common::response("302 Found", &[("Location", "/next")], b""));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.redirect_handler =
+        Some(Arc::new(Mutex::new(|_: &Url, _: u16, _: &HttpResponseHead| RedirectAction::Error)));
+
+    assert!(matches!(request.send(), Err(HttpRequestError::RedirectError { .. })));
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_location() {
+    let (base, server) =
+        common::serve(1, |_, _| common::response("302 Found", &[("Location", "../next?a=1")], b""));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(format!("{}/dir/page", base)).unwrap();
+
+    request.options.max_redirect_count = 0;
+
+    let response = request.send().unwrap();
+
+    assert_eq!(format!("{}/dir/page", base), response.url.as_str());
+    assert_eq!(format!("{}/next?a=1", base), response.location().unwrap().unwrap().as_str());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_redirect_body_replay() {
+    let (base, server) = common::serve(4, |i, _| {
+        match i {
+            0 => common::response("307 Temporary Redirect", &[("Location", "/a")], b""),
+            1 => common::response("308 Permanent Redirect", &[("Location", "/b")], b""),
+            2 => common::response("303 See Other", &[("Location", "/c")], b""),
+            _ => common::response("200 OK", &[], b""),
+        }
+    });
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    request.body = Some(HttpRequestBody::Raw(b"payload".to_vec()));
+
+    request.send().unwrap();
+
+    let requests = server.join().unwrap();
+
+    for request in &requests[..3] {
+        assert_eq!("POST", request.method);
+        assert_eq!(b"payload".to_vec(), request.body);
+    }
+
+    assert_eq!("GET", requests[3].method);
+    assert!(requests[3].body.is_empty());
+}
+
+#[test]
+fn test_request_method_and_url() {
+    let (base, server) = common::serve(2, |i, _| {
+        match i {
+            0 => common::response("303 See Other", &[("Location", "/c")], b""),
+            _ => common::response("200 OK", &[], b""),
+        }
+    });
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    request.add_query("q".to_string(), "1".to_string());
+
+    let response = request.send().unwrap();
+
+    // the original request, whereas `url` is the one of the last redirection
+    assert_eq!(HttpRequestMethod::POST, response.request_method);
+    assert_eq!(format!("{}/?q=1", base), response.request_url.as_str());
+    assert_eq!(format!("{}/c?q=1", base), response.url.as_str());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_redirect_behavior() {
+    let (base, server) = common::serve(3, |i, _| {
+        match i {
+            0 => common::response("302 Found", &[("Location", "/a")], b""),
+            1 => common::response("399 Custom", &[("Location", "/b")], b""),
+            _ => common::response("301 Moved Permanently", &[("Location", "/c")], b""),
+        }
+    });
+
+    let mut request = DefaultHttpRequest::post_from_url_str(&base).unwrap();
+
+    request.body = Some(HttpRequestBody::Raw(b"payload".to_vec()));
+    request.options.redirect_behavior.insert(302, RedirectMethodPolicy::Get);
+    request.options.redirect_behavior.insert(399, RedirectMethodPolicy::Keep);
+    request.options.redirect_behavior.remove(&301);
+
+    let response = request.send().unwrap();
+
+    assert_eq!(301, response.status_code);
+
+    let requests = server.join().unwrap();
+
+    assert_eq!("GET", requests[1].method);
+    assert_eq!("/a", requests[1].target);
+    assert_eq!("GET", requests[2].method);
+    assert_eq!("/b", requests[2].target);
+}
+
+#[test]
+fn test_follow() {
+    let (base, server) = common::serve(3, |i, _| {
+        match i {
+            0 => common::response("303 See Other", &[("Location", "/a")], b""),
+            1 => common::response("302 Found", &[("Location", "/b")], b""),
+            _ => common::response("200 OK", &[], b"done"),
+        }
+    });
+
+    let mut request = DefaultHttpRequest::put_from_url_str(&base).unwrap();
+
+    request.options.max_redirect_count = 0;
+
+    let options = request.options.clone();
+
+    let response = request.send().unwrap();
+
+    assert_eq!(303, response.status_code);
+
+    let response = response.follow(&options).unwrap();
+
+    assert_eq!(302, response.status_code);
+    assert!(!response.was_redirected);
+
+    let response = response.follow(&options).unwrap();
+
+    assert_eq!(b"done".to_vec(), response.body);
+
+    match response.follow(&options) {
+        Err(HttpRequestError::RedirectError {
+            status: Some(200),
+            ..
+        }) => (),
+        other => panic!("{:?}", other),
+    }
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(("GET", "/a"), (requests[1].method.as_str(), requests[1].target.as_str()));
+    assert_eq!(("GET", "/b"), (requests[2].method.as_str(), requests[2].target.as_str()));
+}
+
+#[test]
+fn test_follow_keep_body() {
+    let (base, server) = common::serve(1, |_, _| {
+        common::response("307 Temporary Redirect", &[("Location", "/a")], b"")
+    });
+
+    let mut request = DefaultHttpRequest::put_from_url_str(&base).unwrap();
+
+    request.body = Some(HttpRequestBody::Raw(b"data".to_vec()));
+    request.options.max_redirect_count = 0;
+
+    let options = request.options.clone();
+
+    let response = request.send().unwrap();
+
+    // the body cannot be sent again, so an empty `PUT` request must not be sent instead
+    match response.follow(&options) {
+        Err(HttpRequestError::RedirectError {
+            status: Some(307),
+            ..
+        }) => (),
+        other => panic!("{:?}", other),
+    }
+
+    assert_eq!(1, server.join().unwrap().len());
+}
+
+#[test]
+fn test_follow_checks() {
+    let (base, server) = common::serve(2, |i, request| {
+        match i {
+            0 => {
+                let port = request.header("Host").unwrap().rsplit(':').next().unwrap();
+
+                let location = format!("http://localhost:{}/other", port);
+
+                common::response("302 Found", &[("Location", location.as_str())], b"")
+            }
+            _ => common::response("302 Found", &[("Location", "/again")], b""),
+        }
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_redirect_count = 0;
+
+    let response = request.send().unwrap();
+
+    let options = HttpRequestOptions {
+        same_host_redirects_only: true,
+        ..HttpRequestOptions::default()
+    };
+
+    match response.follow(&options) {
+        Err(HttpRequestError::RedirectError {
+            status: Some(302),
+            ..
+        }) => (),
+        other => panic!("{:?}", other),
+    }
+
+    // only one request is sent, although the options would follow redirections
+    let response = response.follow(&HttpRequestOptions::default()).unwrap();
+
+    assert_eq!(302, response.status_code);
+    assert_eq!(Some("/again"), response.header("Location"));
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(2, requests.len());
+    assert_eq!("/other", requests[1].target);
+}
@@ -0,0 +1,192 @@
+extern crate easy_http_request;
+
+mod common;
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use easy_http_request::{DefaultHttpRequest, HttpRequestError, HttpSession};
+
+#[test]
+fn test_default_headers() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], b""));
+
+    let mut session = HttpSession::new();
+
+    session.headers.insert("X-Api-Key".to_string(), "secret".to_string());
+    session.headers.insert("Accept".to_string(), "text/plain".to_string());
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    let mut headers = HashMap::new();
+
+    headers.insert("accept".to_string(), "application/json".to_string());
+
+    request.headers = Some(headers);
+
+    session.send(request).unwrap();
+
+    let requests = server.join().unwrap();
+
+    assert_eq!(Some("secret"), requests[0].header("X-Api-Key"));
+    assert_eq!(Some("application/json"), requests[0].header("Accept"));
+    assert_eq!(
+        1,
+        requests[0].headers.iter().filter(|(k, _)| k.eq_ignore_ascii_case("Accept")).count()
+    );
+}
+
+#[test]
+fn test_connection_reuse() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    // a keep-alive server which answers every request on a connection until the client closes it
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let mut targets = Vec::new();
+
+        for i in 0..3 {
+            let request = common::read_request(&mut stream);
+
+            targets.push(request.target);
+
+            let body = format!("response {}", i);
+
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)
+                .unwrap();
+        }
+
+        targets
+    });
+
+    let session = HttpSession::new();
+
+    for i in 0..3 {
+        let request = DefaultHttpRequest::get_from_url_str(format!("{}/{}", base, i)).unwrap();
+
+        let response = session.send(request).unwrap();
+
+        assert_eq!(format!("response {}", i).into_bytes(), response.body);
+        assert_eq!(1, session.connection_pool.idle_count());
+    }
+
+    // every request has been answered on the only accepted connection
+    assert_eq!(vec!["/0", "/1", "/2"], server.join().unwrap());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_connection_reuse_per_target() {
+    // two keep-alive servers on the same port of different loopback addresses
+    let first = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = first.local_addr().unwrap().port();
+    let second = TcpListener::bind(("127.0.0.2", port)).unwrap();
+
+    let servers: Vec<_> = vec![first, second]
+        .into_iter()
+        .enumerate()
+        .map(|(i, listener)| {
+            thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+
+                common::read_request(&mut stream);
+
+                let body = format!("server {}", i);
+
+                write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)
+                    .unwrap();
+
+                // keep the connection open until the client closes it
+                let _ = stream.read_to_end(&mut Vec::new());
+            })
+        })
+        .collect();
+
+    let session = HttpSession::new();
+
+    for (i, ip) in [Ipv4Addr::new(127, 0, 0, 1), Ipv4Addr::new(127, 0, 0, 2)].iter().enumerate() {
+        let mut request =
+            DefaultHttpRequest::get_from_url_str(format!("http://backend.test:{}/", port)).unwrap();
+
+        request.options.resolve.push(("backend.test".to_string(), IpAddr::V4(*ip)));
+        // a connection to the wrong server would not be answered
+        request.options.idle_read_timeout = Some(Duration::from_secs(2));
+
+        let response = session.send(request).unwrap();
+
+        assert_eq!(format!("server {}", i).into_bytes(), response.body);
+    }
+
+    assert_eq!(2, session.connection_pool.idle_count());
+
+    session.connection_pool.clear();
+
+    for server in servers {
+        server.join().unwrap();
+    }
+}
+
+#[test]
+fn test_max_connections_per_host() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    let (arrived_sender, arrived_receiver) = mpsc::channel();
+    let (go_sender, go_receiver) = mpsc::channel();
+
+    // the first request is answered only after the second one has been attempted
+    let server = thread::spawn(move || {
+        for i in 0..2 {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            common::read_request(&mut stream);
+
+            if i == 0 {
+                arrived_sender.send(()).unwrap();
+                go_receiver.recv().unwrap();
+            }
+
+            stream.write_all(&common::response("200 OK", &[], b"")).unwrap();
+        }
+    });
+
+    let mut session = HttpSession::new();
+
+    session.max_connections_per_host = Some(1);
+    session.error_on_connection_limit = true;
+
+    let session = Arc::new(session);
+
+    let first = {
+        let session = session.clone();
+        let request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+        thread::spawn(move || session.send(request).unwrap())
+    };
+
+    arrived_receiver.recv().unwrap();
+
+    match session.send(DefaultHttpRequest::get_from_url_str(&base).unwrap()) {
+        Err(HttpRequestError::TooManyConnections {
+            limit: 1,
+        }) => (),
+        other => panic!("{:?}", other),
+    }
+
+    go_sender.send(()).unwrap();
+
+    assert_eq!(200, first.join().unwrap().status_code);
+
+    assert_eq!(
+        200,
+        session.send(DefaultHttpRequest::get_from_url_str(&base).unwrap()).unwrap().status_code
+    );
+
+    server.join().unwrap();
+}
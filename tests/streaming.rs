@@ -0,0 +1,239 @@
+extern crate easy_http_request;
+extern crate flate2;
+
+mod common;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+use easy_http_request::{DefaultHttpRequest, HttpRequestError, SseEvent};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+#[test]
+fn test_send_streaming() {
+    let (base, server) = common::serve(1, |_, _| {
+        b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nTransfer-Encoding: chunked\r\n\
+          Connection: close\r\n\r\n8\r\n{\"a\":1}\n\r\n8\r\n{\"a\":2}\n\r\n0\r\n\r\n"
+            .to_vec()
+    });
+
+    let (head, reader) =
+        DefaultHttpRequest::get_from_url_str(&base).unwrap().send_streaming().unwrap();
+
+    assert_eq!(200, head.status_code);
+    assert_eq!(Some("application/x-ndjson"), head.header("Content-Type"));
+
+    let lines: Vec<String> = BufReader::new(reader).lines().map(|line| line.unwrap()).collect();
+
+    assert_eq!(vec!["{\"a\":1}".to_string(), "{\"a\":2}".to_string()], lines);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_streaming_limit() {
+    let (base, server) = common::serve(1, |_, _| common::response("200 OK", &[], &[b'a'; 2048]));
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_response_body_size = Some(1024);
+
+    let (_, mut reader) = request.send_streaming().unwrap();
+
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+    match err.into_inner().unwrap().downcast::<HttpRequestError>().map(|err| *err) {
+        Ok(HttpRequestError::TooLarge {
+            limit,
+            ..
+        }) => assert_eq!(1024, limit),
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_into() {
+    let (base, server) = common::serve(2, |i, _| {
+        match i {
+            0 => common::response("200 OK", &[("X-Test", "1")], b"first"),
+            _ => common::response("200 OK", &[], &[b'a'; 100]),
+        }
+    });
+
+    let mut buf = Vec::with_capacity(64);
+
+    let head = DefaultHttpRequest::get_from_url_str(&base).unwrap().send_into(&mut buf).unwrap();
+
+    assert_eq!(200, head.status_code);
+    assert_eq!(Some("1"), head.header("X-Test"));
+    assert_eq!(b"first".to_vec(), buf);
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.max_response_body_size = Some(10);
+
+    assert!(matches!(request.send_into(&mut buf), Err(HttpRequestError::TooLarge { .. })));
+    assert!(buf.len() <= 10);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_decompressed_to_writer() {
+    let text = "log line\n".repeat(1000);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let (base, server) = common::serve(1, move |_, _| {
+        common::response("200 OK", &[("Content-Encoding", "gzip")], &compressed)
+    });
+
+    let mut output = Vec::new();
+
+    let head = DefaultHttpRequest::get_from_url_str(&base)
+        .unwrap()
+        .send_decompressed_to_writer(&mut output)
+        .unwrap();
+
+    assert_eq!(200, head.status_code);
+    assert_eq!(text.as_bytes(), output.as_slice());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_event_stream() {
+    let (base, server) = common::serve(1, |_, _| {
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\
+          Connection: close\r\n\r\n\
+          1a\r\n: keep-alive\ndata: first\n\n\r\n\
+          29\r\nevent: update\r\nid: 7\r\ndata: a\r\ndata:b\r\n\r\n\r\n\
+          19\r\nretry: 3000\ndata: third\n\n\r\n\
+          a\r\ndata: lost\r\n0\r\n\r\n"
+            .to_vec()
+    });
+
+    let events = DefaultHttpRequest::get_from_url_str(&base).unwrap().send_event_stream().unwrap();
+
+    assert_eq!(Some("text/event-stream"), events.head().header("Content-Type"));
+
+    let events: Vec<SseEvent> = events.map(|event| event.unwrap()).collect();
+
+    assert_eq!(
+        vec![
+            SseEvent {
+                event: "message".to_string(),
+                data: "first".to_string(),
+                id: None,
+                retry: None,
+            },
+            SseEvent {
+                event: "update".to_string(),
+                data: "a\nb".to_string(),
+                id: Some("7".to_string()),
+                retry: None,
+            },
+            SseEvent {
+                event: "message".to_string(),
+                data: "third".to_string(),
+                id: Some("7".to_string()),
+                retry: Some(Duration::from_millis(3000)),
+            },
+        ],
+        events
+    );
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_event_stream_carriage_return() {
+    // a `\r\n` split between two chunks, and lines which end with a bare `\r`
+    let (base, server) = common::serve(1, |_, _| {
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\
+          Connection: close\r\n\r\n8\r\ndata: a\r\r\na\r\n\ndata: b\r\r\r\n0\r\n\r\n"
+            .to_vec()
+    });
+
+    let events: Vec<String> = DefaultHttpRequest::get_from_url_str(&base)
+        .unwrap()
+        .send_event_stream()
+        .unwrap()
+        .map(|event| event.unwrap().data)
+        .collect();
+
+    assert_eq!(vec!["a\nb".to_string()], events);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_event_stream_too_large() {
+    let bodies = [vec![b'a'; 4096], b"data: aaaaaaaaaaaaaaaa\n".repeat(256)];
+
+    for body in bodies.iter() {
+        let body = body.clone();
+
+        let (base, server) = common::serve(1, move |_, _| {
+            common::response("200 OK", &[("Content-Type", "text/event-stream")], &body)
+        });
+
+        let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+        // the size of a line or an event is limited anyway
+        request.options.max_response_body_size = None;
+
+        let mut events = request.send_event_stream().unwrap().max_event_size(1024);
+
+        assert!(matches!(
+            events.next(),
+            Some(Err(HttpRequestError::TooLarge {
+                limit: 1024,
+                ..
+            }))
+        ));
+        assert!(events.next().is_none());
+
+        server.join().unwrap();
+    }
+}
+
+#[test]
+fn test_send_event_stream_idle_read_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        common::read_request(&mut stream);
+
+        let _ = stream.write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n\
+              data: first\n\n",
+        );
+        let _ = stream.flush();
+
+        thread::sleep(Duration::from_millis(1000));
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(url).unwrap();
+
+    request.options.idle_read_timeout = Some(Duration::from_millis(200));
+
+    let mut events = request.send_event_stream().unwrap();
+
+    assert_eq!("first", events.next().unwrap().unwrap().data);
+    assert!(matches!(events.next(), Some(Err(HttpRequestError::TimeOut(_)))));
+    assert!(events.next().is_none());
+
+    server.join().unwrap();
+}
@@ -0,0 +1,164 @@
+extern crate easy_http_request;
+
+mod common;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use easy_http_request::{DefaultHttpRequest, HttpRequestError};
+
+#[test]
+fn test_deadline() {
+    let (base, server) = common::serve(1, |_, _| {
+        thread::sleep(Duration::from_millis(1000));
+
+        common::response("200 OK", &[], b"")
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.deadline = Some(Instant::now() - Duration::from_millis(1));
+
+    match request.send_preserved() {
+        Err(HttpRequestError::TimeOut(_)) => (),
+        other => panic!("{:?}", other),
+    }
+
+    request.options.deadline = Some(Instant::now() + Duration::from_millis(200));
+
+    match request.send() {
+        Err(HttpRequestError::TimeOut(elapsed)) => assert!(elapsed < Duration::from_millis(1000)),
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_tls_handshake_timeout() {
+    // the server accepts the TCP connection but never answers the TLS handshake
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("https://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let (_stream, _) = listener.accept().unwrap();
+
+        thread::sleep(Duration::from_millis(1000));
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(url).unwrap();
+
+    request.options.tls_handshake_timeout = Some(Duration::from_millis(200));
+
+    match request.send() {
+        Err(HttpRequestError::TimeOut(elapsed)) => assert!(elapsed < Duration::from_millis(1000)),
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_collect_timings() {
+    let (base, server) = common::serve(2, |i, _| {
+        if i == 1 {
+            thread::sleep(Duration::from_millis(200));
+        }
+
+        common::response("200 OK", &[], b"")
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    assert!(request.send_preserved().unwrap().timings.is_none());
+
+    request.options.collect_timings = true;
+
+    let timings = request.send().unwrap().timings.unwrap();
+
+    // the host is an IP address
+    assert!(timings.dns.is_none());
+    assert!(timings.tls.is_none());
+    assert!(timings.time_to_first_byte >= Duration::from_millis(200));
+    assert!(timings.connect <= timings.time_to_first_byte);
+    assert!(timings.time_to_first_byte <= timings.total);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_idle_read_timeout() {
+    // the server trickles the body
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        common::read_request(&mut stream);
+
+        let _ =
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\na");
+        let _ = stream.flush();
+
+        thread::sleep(Duration::from_millis(1000));
+
+        let _ = stream.write_all(b"b");
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(url).unwrap();
+
+    request.options.idle_read_timeout = Some(Duration::from_millis(200));
+
+    match request.send() {
+        Err(HttpRequestError::TimeOut(elapsed)) => assert!(elapsed < Duration::from_millis(1000)),
+        other => panic!("{:?}", other),
+    }
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_cancel_token() {
+    let cancel_token = Arc::new(AtomicBool::new(true));
+
+    let mut request = DefaultHttpRequest::get_from_url_str("http://127.0.0.1:1/").unwrap();
+
+    request.options.cancel_token = Some(cancel_token.clone());
+
+    // nothing is connected once the flag has been set
+    assert!(matches!(request.send(), Err(HttpRequestError::Cancelled)));
+
+    let (base, server) = common::serve(1, |_, _| {
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n5\r\nfirst\r\n\
+          6\r\nsecond\r\n0\r\n\r\n"
+            .to_vec()
+    });
+
+    cancel_token.store(false, Ordering::Relaxed);
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.options.cancel_token = Some(cancel_token.clone());
+
+    let (_, mut reader) = request.send_streaming().unwrap();
+
+    let mut buffer = [0u8; 5];
+
+    reader.read_exact(&mut buffer).unwrap();
+
+    cancel_token.store(true, Ordering::Relaxed);
+
+    let err = reader.read(&mut buffer).unwrap_err();
+
+    assert!(matches!(
+        err.into_inner().unwrap().downcast::<HttpRequestError>().map(|err| *err),
+        Ok(HttpRequestError::Cancelled)
+    ));
+
+    server.join().unwrap();
+}
@@ -0,0 +1,46 @@
+#![cfg(unix)]
+
+extern crate easy_http_request;
+
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::os::unix::net::UnixListener;
+use std::thread;
+
+use easy_http_request::DefaultHttpRequest;
+
+#[test]
+fn test_unix_socket() {
+    let path = std::env::temp_dir().join(format!("easy-http-request-{}.sock", std::process::id()));
+
+    let _ = fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let request = common::read_request(&mut stream);
+
+        stream.write_all(&common::response("200 OK", &[], b"unix")).unwrap();
+
+        request
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str("http://docker/info").unwrap();
+
+    request.options.unix_socket = Some(path.clone());
+
+    let response = request.send().unwrap();
+
+    assert_eq!(b"unix", response.body.as_slice());
+
+    let request = server.join().unwrap();
+
+    assert_eq!("/info", request.target);
+    assert_eq!(Some("docker"), request.header("Host"));
+
+    fs::remove_file(&path).unwrap();
+}
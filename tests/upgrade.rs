@@ -0,0 +1,130 @@
+extern crate easy_http_request;
+
+mod common;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use easy_http_request::DefaultHttpRequest;
+
+fn serve_upgrade() -> (String, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let request = common::read_request(&mut stream);
+
+        assert_eq!(Some("test"), request.header("Upgrade"));
+
+        stream
+            .write_all(
+                b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: test\r\nConnection: Upgrade\r\n\r\n",
+            )
+            .unwrap();
+
+        let mut ping = [0; 4];
+
+        // the client may not speak the new protocol
+        if stream.read_exact(&mut ping).is_ok() {
+            assert_eq!(b"ping", &ping);
+
+            stream.write_all(b"pong").unwrap();
+        }
+    });
+
+    (base, server)
+}
+
+#[test]
+fn test_switching_protocols() {
+    let (base, server) = serve_upgrade();
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.set_header("Connection".to_string(), "Upgrade".to_string());
+    request.set_header("Upgrade".to_string(), "test".to_string());
+
+    let response = request.send().unwrap();
+
+    assert_eq!(101, response.status_code);
+    assert_eq!(Some("test"), response.header("Upgrade"));
+    assert!(response.body.is_empty());
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_upgrade() {
+    let (base, server) = serve_upgrade();
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.set_header("Connection".to_string(), "Upgrade".to_string());
+    request.set_header("Upgrade".to_string(), "test".to_string());
+
+    let (head, stream) = request.send_upgrade().unwrap();
+
+    assert_eq!(101, head.status_code);
+
+    let mut stream = stream.unwrap();
+
+    stream.write_all(b"ping").unwrap();
+
+    let mut pong = [0; 4];
+    stream.read_exact(&mut pong).unwrap();
+
+    assert_eq!(b"pong", &pong);
+
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_upgrade_early_data() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        common::read_request(&mut stream);
+
+        // the first frame of the new protocol is sent along with the headers
+        stream
+            .write_all(
+                b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: test\r\nConnection: \
+                  Upgrade\r\n\r\nhello",
+            )
+            .unwrap();
+
+        let mut ping = [0; 4];
+        stream.read_exact(&mut ping).unwrap();
+
+        stream.write_all(b"pong").unwrap();
+    });
+
+    let mut request = DefaultHttpRequest::get_from_url_str(&base).unwrap();
+
+    request.set_header("Connection".to_string(), "Upgrade".to_string());
+    request.set_header("Upgrade".to_string(), "test".to_string());
+
+    let (_, stream) = request.send_upgrade().unwrap();
+
+    let mut stream = stream.unwrap();
+
+    let mut hello = [0; 5];
+    stream.read_exact(&mut hello).unwrap();
+
+    assert_eq!(b"hello", &hello);
+
+    stream.write_all(b"ping").unwrap();
+
+    let mut pong = [0; 4];
+    stream.read_exact(&mut pong).unwrap();
+
+    assert_eq!(b"pong", &pong);
+
+    server.join().unwrap();
+}